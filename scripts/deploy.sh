@@ -12,15 +12,16 @@ printf "\n>> Building & deploying dispute contract"
 ./scripts/build.sh dispute
 near deploy --wasmFile res/dispute.wasm --accountId $DISPUTE_CONTRACT --initFunction new --initArgs '{}'
 
-printf "\n>> Adding dispute contract address to store contract then building it"
-sed -i -e "s/ddd7.testnet/$DISPUTE_CONTRACT/g" store/src/lib.rs
+printf "\n>> Building store contract"
 ./scripts/build.sh store
-sed -i -e "s/$DISPUTE_CONTRACT/ddd7.testnet/g" store/src/lib.rs
 
 printf "\n>> Building & deploying store factory contract"
 ./scripts/build.sh store-factory
 near deploy --wasmFile res/store_factory.wasm --accountId $FACTORY_CONTRACT --initFunction new --initArgs '{}'
 
+printf "\n>> Allowing stores to use the dispute contract as arbiter"
+near call $FACTORY_CONTRACT add_arbiter "{\"arbiter_id\": \"$DISPUTE_CONTRACT\"}" --accountId $FACTORY_CONTRACT
+
 echo "
 
 
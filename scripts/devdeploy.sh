@@ -27,11 +27,9 @@ near dev-deploy --wasmFile res/dispute.wasm --projectKeyDirectory neardev/disput
 DISPUTE_CONTRACT=$(<neardev/dispute/dev-account)
 } & spinner
 
-printf "\n>> Adding dispute contract address to store contract then building it"
+printf "\n>> Building store contract"
 {
-sed -i -e "s/ddd7.testnet/$DISPUTE_CONTRACT/g" store/src/lib.rs
 ./scripts/build.sh store
-sed -i -e "s/$DISPUTE_CONTRACT/ddd7.testnet/g" store/src/lib.rs
 } &> /dev/null & spinner
 
 printf "\n>> Building & deploying store factory contract"
@@ -41,6 +39,13 @@ near dev-deploy --wasmFile res/store_factory.wasm --projectKeyDirectory neardev/
 FACTORY_CONTRACT=$(<neardev/store_factory/dev-account)
 } &> /dev/null & spinner
 
+printf "\n>> Allowing stores to use the dispute contract as arbiter"
+{
+DISPUTE_CONTRACT=$(<neardev/dispute/dev-account)
+FACTORY_CONTRACT=$(<neardev/store_factory/dev-account)
+near call $FACTORY_CONTRACT add_arbiter "{\"arbiter_id\": \"$DISPUTE_CONTRACT\"}" --accountId $FACTORY_CONTRACT
+} &> /dev/null & spinner
+
 echo "
 
 
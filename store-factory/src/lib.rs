@@ -16,6 +16,12 @@ type Version = [u8; 2];
 const DEFAULT_CODE_HASH_KEY: &[u8; 4] = b"CODE";
 const FACTORY_OWNER_KEY: &[u8; 5] = b"OWNER";
 const CODE_METADATA_KEY: &[u8; 8] = b"METADATA";
+const TREASURY_KEY: &[u8; 8] = b"TREASURY";
+const COMMISSION_KEY: &[u8; 10] = b"COMMISSION";
+const ARBITERS_KEY: &[u8; 8] = b"ARBITERS";
+
+// Commissions are expressed in basis points, 10000 being the whole amount.
+const MAX_BASIS_POINTS: u16 = 10_000;
 
 // The values used when writing initial data to the storage.
 const CONTRACT_INITIAL_CODE: &[u8] = include_bytes!("../../res/store.wasm");
@@ -125,14 +131,37 @@ impl StoreFactory {
             .unwrap();
 
         // add owner_id to the args argument
-        let owner_id = serde_json::from_slice::<serde_json::Value>(&args.0)
-            .expect("Failed to deserialize")
+        let mut args = serde_json::from_slice::<serde_json::Value>(&args.0)
+            .expect("Failed to deserialize");
+        let owner_id = args
             .get("owner_id")
             .expect("owner_id not found")
             .as_str()
             .expect("owner_id is not a string")
             .to_string();
 
+        // the arbiter is chosen by the store creator among the ones the factory allows
+        let arbiter_id: AccountId = args
+            .get("arbiter_id")
+            .expect("arbiter_id not found")
+            .as_str()
+            .expect("arbiter_id is not a string")
+            .parse()
+            .expect("arbiter_id is not a valid account ID");
+        assert!(
+            self.is_arbiter_allowed(arbiter_id),
+            "Arbiter is not allowed by the factory"
+        );
+
+        // the marketplace commission is set by the factory, not by the store creator
+        let args_map = args.as_object_mut().expect("args is not an object");
+        args_map.insert("treasury_id".to_string(), json!(self.get_treasury()));
+        args_map.insert(
+            "commission_bps".to_string(),
+            json!(self.get_commission_bps()),
+        );
+        let args = serde_json::to_vec(&args).expect("Failed to serialize");
+
         let callback_args = serde_json::to_vec(&json!({
             "account_id": account_id,
             "attached_deposit": U128(env::attached_deposit()),
@@ -144,7 +173,7 @@ impl StoreFactory {
             self.get_default_code_hash(),
             account_id,
             "new",
-            &args.0,
+            &args,
             "on_create",
             &callback_args,
         );
@@ -212,6 +241,36 @@ impl StoreFactory {
             .insert(&owner_id, &stores_for_creator);
     }
 
+    /// Moves the store to the set of its new owner, called by the store when ownership is transferred
+    pub fn transfer_store(
+        &mut self,
+        store_id: AccountId,
+        old_owner_id: AccountId,
+        new_owner_id: AccountId,
+    ) {
+        assert!(
+            env::predecessor_account_id() == store_id,
+            "Must be transferred by the Store itself"
+        );
+        assert!(self.stores.contains(&store_id), "Store does not exist");
+
+        if let Some(mut stores_for_creator) = self.stores_for_creator.get(&old_owner_id) {
+            stores_for_creator.remove(&store_id);
+            self.stores_for_creator
+                .insert(&old_owner_id, &stores_for_creator);
+        }
+
+        let mut stores_for_creator =
+            self.stores_for_creator
+                .get(&new_owner_id)
+                .unwrap_or_else(|| {
+                    UnorderedSet::new(format!("sc{}", new_owner_id).as_bytes().to_vec())
+                });
+        stores_for_creator.insert(&store_id);
+        self.stores_for_creator
+            .insert(&new_owner_id, &stores_for_creator);
+    }
+
     /**************/
     /*** Stores ***/
     /**************/
@@ -307,6 +366,75 @@ impl StoreFactory {
         default_metadata.version
     }
 
+    /******************/
+    /*** Commission ***/
+    /******************/
+
+    /// Set the account receiving the commission of stores created from now on
+    pub fn set_treasury(&self, treasury_id: AccountId) {
+        self.assert_owner();
+        env::storage_write(TREASURY_KEY, treasury_id.as_bytes());
+    }
+    /// Get the treasury, defaults to the factory owner
+    pub fn get_treasury(&self) -> AccountId {
+        env::storage_read(TREASURY_KEY)
+            .map(|treasury_id| {
+                AccountId::new_unchecked(String::from_utf8(treasury_id).expect("INTERNAL_FAIL"))
+            })
+            .unwrap_or_else(|| self.get_owner())
+    }
+    /// Set the commission in basis points of stores created from now on
+    pub fn set_commission_bps(&self, commission_bps: u16) {
+        self.assert_owner();
+        assert!(
+            commission_bps <= MAX_BASIS_POINTS,
+            "Commission can't be more than 10000 basis points"
+        );
+        env::storage_write(COMMISSION_KEY, &commission_bps.to_le_bytes());
+    }
+    /// Get the commission in basis points, defaults to 0
+    pub fn get_commission_bps(&self) -> u16 {
+        env::storage_read(COMMISSION_KEY)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .unwrap_or(0)
+    }
+
+    /****************/
+    /*** Arbiters ***/
+    /****************/
+
+    /// Allow stores to use the arbiter, at creation or when they change it
+    pub fn add_arbiter(&self, arbiter_id: AccountId) {
+        self.assert_owner();
+        let mut arbiters = self.internal_arbiters();
+        arbiters.insert(&arbiter_id);
+        self.internal_save_arbiters(&arbiters);
+    }
+    /// Stop allowing the arbiter, stores already using it keep it
+    pub fn remove_arbiter(&self, arbiter_id: AccountId) {
+        self.assert_owner();
+        let mut arbiters = self.internal_arbiters();
+        arbiters.remove(&arbiter_id);
+        self.internal_save_arbiters(&arbiters);
+    }
+    /// Get the allowed arbiters
+    pub fn get_arbiters(&self) -> Vec<AccountId> {
+        self.internal_arbiters().to_vec()
+    }
+    /// Whether stores can use the arbiter
+    pub fn is_arbiter_allowed(&self, arbiter_id: AccountId) -> bool {
+        self.internal_arbiters().contains(&arbiter_id)
+    }
+    fn internal_arbiters(&self) -> UnorderedSet<AccountId> {
+        env::storage_read(ARBITERS_KEY)
+            .map(|arbiters| BorshDeserialize::try_from_slice(&arbiters).expect("INTERNAL_FAIL"))
+            .unwrap_or_else(|| UnorderedSet::new(b"a".to_vec()))
+    }
+    fn internal_save_arbiters(&self, arbiters: &UnorderedSet<AccountId>) {
+        let serialized_arbiters = BorshSerialize::try_to_vec(arbiters).expect("INTERNAL_FAIL");
+        env::storage_write(ARBITERS_KEY, &serialized_arbiters);
+    }
+
     /*****************/
     /*** Ownership ***/
     /*****************/
@@ -0,0 +1,138 @@
+/**
+ *  Arbiter
+ *
+ * Methods:
+ *
+ * - get_pending_arbiter
+ *
+ * - arbiter_propose
+ * - arbiter_propose_callback
+ * - arbiter_cancel
+ *
+ */
+use crate::*;
+
+pub trait ArbiterProvider {
+    fn get_pending_arbiter(&self) -> Option<AccountId>;
+}
+
+#[near_bindgen]
+impl ArbiterProvider for Contract {
+    fn get_pending_arbiter(&self) -> Option<AccountId> {
+        self.pending_arbiter_id.clone()
+    }
+}
+
+pub trait ArbiterManager {
+    fn arbiter_propose(&mut self, arbiter_id: AccountId) -> Promise;
+    fn arbiter_propose_callback(
+        &mut self,
+        arbiter_id: AccountId,
+        call_result: Result<bool, PromiseError>,
+    ) -> bool;
+    fn arbiter_cancel(&mut self);
+}
+
+#[near_bindgen]
+impl ArbiterManager for Contract {
+    fn arbiter_propose(&mut self, arbiter_id: AccountId) -> Promise {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can change the arbiter"
+        );
+        require!(
+            arbiter_id != self.arbiter_id,
+            "Arbiter is already the store arbiter"
+        );
+
+        // ask the factory if the arbiter is allowed
+        let args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "arbiter_id": arbiter_id,
+        }))
+        .unwrap();
+        let promise = Promise::new(self.internal_factory_id()).function_call(
+            "is_arbiter_allowed".to_string(),
+            args,
+            0,
+            Gas::ONE_TERA * 5,
+        );
+
+        // callback to store contract
+        let callback_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "arbiter_id": arbiter_id,
+        }))
+        .expect("Failed to serialize callback args");
+
+        promise.then(Promise::new(env::current_account_id()).function_call(
+            "arbiter_propose_callback".to_string(),
+            callback_args,
+            0,
+            Gas::ONE_TERA * 10,
+        ))
+    }
+
+    #[private]
+    fn arbiter_propose_callback(
+        &mut self,
+        arbiter_id: AccountId,
+        #[callback_result] call_result: Result<bool, PromiseError>,
+    ) -> bool {
+        //the proposal is dropped if the factory doesn't allow the arbiter or can't be reached
+        if call_result != Ok(true) {
+            return false;
+        }
+
+        //a new proposal replaces the previous one
+        self.pending_arbiter_id = Some(arbiter_id.clone());
+
+        // Emit NearEvent, the owner proposed it
+        NearEvent::store_arbiter_propose(ArbiterProposeData::new(arbiter_id))
+            .actor(self.owner_id.clone())
+            .emit();
+
+        //applies right away unless the current arbiter still has disputes
+        self.internal_apply_pending_arbiter();
+        true
+    }
+
+    fn arbiter_cancel(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can cancel the arbiter change"
+        );
+        require!(
+            self.pending_arbiter_id.take().is_some(),
+            "No arbiter change was proposed"
+        );
+
+        // Emit NearEvent
+        NearEvent::store_arbiter_cancel().emit();
+    }
+}
+
+impl Contract {
+    /// Switches to the proposed arbiter once no order waits on the current one.
+    pub(crate) fn internal_apply_pending_arbiter(&mut self) {
+        if self.pending_arbiter_id.is_none() {
+            return;
+        }
+
+        //disputes in progress and settlements on their way stay with the current arbiter
+        if self.open_disputes > 0 {
+            return;
+        }
+
+        self.arbiter_id = self.pending_arbiter_id.take().unwrap();
+
+        // Emit NearEvent, the owner's proposal takes effect whoever closed the last dispute
+        NearEvent::store_update(StoreUpdateData::new(
+            self.owner_id.clone(),
+            self.arbiter_id.clone(),
+            self.metadata.get().unwrap(),
+        ))
+        .actor(self.owner_id.clone())
+        .emit();
+    }
+}
@@ -0,0 +1,311 @@
+/**
+ *  Auction
+ *
+ * Methods:
+ *
+ * - auction_start
+ * - auction_cancel
+ * - auction_bid
+ * - auction_settle
+ *
+ */
+use crate::*;
+
+// English auction of one unit of an item, the highest bid is held in escrow
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Auction {
+    pub reserve_price: Balance,
+    pub min_increment: Balance,
+    pub ends_at: u64,   // timestamp in ms
+    pub extension: u64, // ms, a bid closer than this to the end pushes the end back
+    pub bidder_id: Option<AccountId>,
+    pub bid: Balance,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonAuction {
+    pub reserve_price: U128,
+    pub min_increment: U128,
+    pub ends_at: u64,
+    pub extension: u64,
+    pub bidder_id: Option<AccountId>,
+    pub bid: U128,
+}
+
+impl Auction {
+    pub fn to_json(&self) -> JsonAuction {
+        JsonAuction {
+            reserve_price: U128(self.reserve_price),
+            min_increment: U128(self.min_increment),
+            ends_at: self.ends_at,
+            extension: self.extension,
+            bidder_id: self.bidder_id.clone(),
+            bid: U128(self.bid),
+        }
+    }
+}
+
+pub trait AuctionManager {
+    fn auction_start(
+        &mut self,
+        item_id: U64,
+        reserve_price: U128,
+        min_increment: U128,
+        ends_at: u64,
+        extension: Option<u64>,
+    );
+    fn auction_cancel(&mut self, item_id: U64);
+    fn auction_bid(&mut self, item_id: U64, amount: U128);
+    fn auction_settle(&mut self, item_id: U64) -> Option<U64>;
+}
+
+#[near_bindgen]
+impl AuctionManager for Contract {
+    #[payable]
+    fn auction_start(
+        &mut self,
+        item_id: U64,
+        reserve_price: U128,
+        min_increment: U128,
+        ends_at: u64,
+        extension: Option<u64>,
+    ) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can start an auction"
+        );
+
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        require!(item.auction.is_none(), "Item is already sold by auction");
+        require!(
+            item.variants.is_empty(),
+            "Items with variants can't be auctioned"
+        );
+        require!(item.is_available(), "Item is not available");
+        require!(
+            ends_at > env::block_timestamp_ms(),
+            "Auction must end in the future"
+        );
+        //an equal bid can't take the lead
+        require!(
+            min_increment.0 > 0,
+            "Minimum increment must be greater than 0"
+        );
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        let extension = extension.unwrap_or(0);
+        item.auction = Some(Auction {
+            reserve_price: reserve_price.into(),
+            min_increment: min_increment.into(),
+            ends_at,
+            extension,
+            bidder_id: None,
+            bid: 0,
+        });
+        self.items_by_id.insert(&item_id.into(), &item);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::auction_start(AuctionStartData::new(
+            item_id,
+            reserve_price,
+            min_increment,
+            ends_at,
+            extension,
+        ))
+        .emit();
+    }
+
+    fn auction_cancel(&mut self, item_id: U64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can cancel an auction"
+        );
+
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        let auction = item.auction.as_ref().expect("Item is not sold by auction");
+        require!(
+            auction.bidder_id.is_none(),
+            "Can't cancel an auction with bids"
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        item.auction = None;
+        self.items_by_id.insert(&item_id.into(), &item);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::auction_settle(AuctionSettleData::new(item_id, None, None, None)).emit();
+    }
+
+    #[payable]
+    fn auction_bid(&mut self, item_id: U64, amount: U128) {
+        let bidder_id = env::predecessor_account_id();
+        require!(self.owner_id != bidder_id, "You can't bid on your own item");
+        self.internal_assert_open();
+
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        require!(item.is_available(), "Item is not available");
+        let auction = item.auction.as_mut().expect("Item is not sold by auction");
+
+        //check if the auction is running
+        let now = env::block_timestamp_ms();
+        require!(now < auction.ends_at, "Auction has ended");
+
+        //check if the bid is high enough
+        let amount: Balance = amount.into();
+        if auction.bidder_id.is_some() {
+            require!(
+                amount >= auction.bid + auction.min_increment,
+                "Bid must be higher than the current bid plus the minimum increment"
+            );
+        } else {
+            require!(
+                amount >= auction.reserve_price,
+                "Bid must be at least the reserve price"
+            );
+        }
+        require!(
+            env::attached_deposit() >= amount,
+            "Not enough deposit for this bid"
+        );
+
+        //late bids push the end back
+        if auction.ends_at - now < auction.extension {
+            auction.ends_at = now + auction.extension;
+        }
+        let ends_at = auction.ends_at;
+
+        //the rest of the deposit goes to the bidder's storage balance, the winner pays for the order
+        let storage_deposit = env::attached_deposit() - amount;
+        if storage_deposit > 0 {
+            self.internal_storage_deposit(&bidder_id, storage_deposit);
+        }
+
+        //the outbid bidder no longer pays for the bytes of the bid
+        let outbid = self.internal_clear_bid(item_id.into(), &mut item);
+
+        //the bid is held in escrow, the leading bidder pays for its bytes
+        self.internal_escrow_deposit(amount, &None);
+        let initial_storage_usage = env::storage_usage();
+        let auction = item.auction.as_mut().unwrap();
+        auction.bidder_id = Some(bidder_id.clone());
+        auction.bid = amount;
+        self.items_by_id.insert(&item_id.into(), &item);
+        self.internal_storage_update(&bidder_id, initial_storage_usage);
+
+        //refund the outbid bidder
+        if let Some((outbid_id, bid)) = outbid {
+            self.internal_payout(outbid_id, bid, &None);
+        }
+
+        // Emit NearEvent
+        NearEvent::auction_bid(AuctionBidData::new(
+            item_id,
+            bidder_id,
+            U128(amount),
+            ends_at,
+        ))
+        .emit();
+    }
+
+    fn auction_settle(&mut self, item_id: U64) -> Option<U64> {
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        let auction = item.auction.as_ref().expect("Item is not sold by auction");
+        require!(
+            env::block_timestamp_ms() >= auction.ends_at,
+            "Auction has not ended yet"
+        );
+
+        let winner = self.internal_clear_bid(item_id.into(), &mut item);
+
+        let initial_storage_usage = env::storage_usage();
+        item.auction = None;
+        self.items_by_id.insert(&item_id.into(), &item);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        //the winner gets a normal order, paid with the escrowed bid, settling can't fail
+        //so the bid is credited back if the owner took the unit out of stock meanwhile
+        let order_id = match &winner {
+            Some((winner_id, bid)) if item.stock > 0 => {
+                self.internal_reserve_stock(item_id.into(), &None, 1, winner_id);
+                let order_id = self.internal_insert_order(
+                    winner_id.clone(),
+                    CartItem {
+                        item_id,
+                        quantity: None,
+                        variant: None,
+                        coupon: None,
+                    },
+                    *bid,
+                    None,
+                    None,
+                    None,
+                );
+                //the winner tops up the storage balance if it doesn't cover the order
+                let storage_bytes = self.orders_by_id.get(&order_id).unwrap().storage_bytes;
+                self.internal_storage_charge_unchecked(winner_id, storage_bytes);
+                Some(U64(order_id))
+            }
+            Some((winner_id, bid)) => {
+                self.internal_payout(winner_id.clone(), *bid, &None);
+                None
+            }
+            None => None,
+        };
+
+        // Emit NearEvent
+        let amount = order_id.and(winner.as_ref().map(|(_, bid)| U128(*bid)));
+        NearEvent::auction_settle(AuctionSettleData::new(
+            item_id,
+            winner.map(|(winner_id, _)| winner_id),
+            amount,
+            order_id,
+        ))
+        .emit();
+
+        order_id
+    }
+}
+
+impl Contract {
+    /// Takes the leading bid off the auction and releases its bytes from the bidder,
+    /// returns the bidder and the escrowed bid.
+    fn internal_clear_bid(
+        &mut self,
+        item_id: u64,
+        item: &mut Item,
+    ) -> Option<(AccountId, Balance)> {
+        let auction = item.auction.as_mut().unwrap();
+        let bidder_id = auction.bidder_id.take()?;
+        let bid = auction.bid;
+
+        let initial_storage_usage = env::storage_usage();
+        auction.bid = 0;
+        self.items_by_id.insert(&item_id, item);
+        self.internal_storage_update(&bidder_id, initial_storage_usage);
+
+        Some((bidder_id, bid))
+    }
+}
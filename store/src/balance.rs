@@ -0,0 +1,259 @@
+/**
+ *  Balances
+ *
+ * Methods:
+ *
+ * - get_claimable
+ * - get_escrow
+ *
+ * - withdraw
+ * - withdraw_callback
+ *
+ */
+use crate::*;
+
+// Funds by token, settled to an account waiting to withdraw them, or the store's totals
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct ClaimableBalance {
+    pub near: Balance,
+    pub ft: HashMap<AccountId, Balance>, // by token
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonClaimableBalance {
+    pub near: U128,
+    pub ft: HashMap<AccountId, U128>,
+}
+
+impl ClaimableBalance {
+    pub fn get(&self, ft_token_id: &Option<AccountId>) -> Balance {
+        match ft_token_id {
+            None => self.near,
+            Some(ft_token_id) => self.ft.get(ft_token_id).copied().unwrap_or(0),
+        }
+    }
+
+    pub fn set(&mut self, ft_token_id: &Option<AccountId>, amount: Balance) {
+        match ft_token_id {
+            None => self.near = amount,
+            Some(ft_token_id) if amount == 0 => {
+                self.ft.remove(ft_token_id);
+            }
+            Some(ft_token_id) => {
+                self.ft.insert(ft_token_id.clone(), amount);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.near == 0 && self.ft.is_empty()
+    }
+}
+
+pub trait BalanceProvider {
+    fn get_claimable(&self, account_id: AccountId) -> JsonClaimableBalance;
+    fn get_escrow(&self, ft_token_id: Option<AccountId>) -> U128;
+}
+
+#[near_bindgen]
+impl BalanceProvider for Contract {
+    fn get_claimable(&self, account_id: AccountId) -> JsonClaimableBalance {
+        let balance = self.balances.get(&account_id).unwrap_or_default();
+        JsonClaimableBalance {
+            near: U128(balance.near),
+            ft: balance
+                .ft
+                .iter()
+                .map(|(ft_token_id, amount)| (ft_token_id.clone(), U128(*amount)))
+                .collect(),
+        }
+    }
+
+    fn get_escrow(&self, ft_token_id: Option<AccountId>) -> U128 {
+        //orders not settled yet, including held return reserves, open offers and bids
+        U128(self.escrow.get(&ft_token_id))
+    }
+}
+
+pub trait BalanceManager {
+    fn withdraw(&mut self, ft_token_id: Option<AccountId>, amount: Option<U128>) -> Promise;
+    fn withdraw_callback(
+        &mut self,
+        account_id: AccountId,
+        amount: U128,
+        ft_token_id: Option<AccountId>,
+    );
+}
+
+#[near_bindgen]
+impl BalanceManager for Contract {
+    #[payable]
+    fn withdraw(&mut self, ft_token_id: Option<AccountId>, amount: Option<U128>) -> Promise {
+        require!(
+            env::attached_deposit() == ONE_YOCTO,
+            "Requires attached deposit of exactly 1 yoctoNEAR"
+        );
+
+        let account_id = env::predecessor_account_id();
+        let available = self
+            .balances
+            .get(&account_id)
+            .unwrap_or_default()
+            .get(&ft_token_id);
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        require!(amount > 0, "Nothing to withdraw");
+        require!(
+            amount <= available,
+            "Can't withdraw more than the claimable balance"
+        );
+
+        //taken out before the transfer, the callback restores it if the transfer fails
+        self.internal_debit(&account_id, amount, &ft_token_id);
+
+        // Emit NearEvent
+        NearEvent::balance_withdraw(BalanceWithdrawData::new(
+            account_id.clone(),
+            U128(amount),
+            ft_token_id.clone(),
+        ))
+        .emit();
+
+        let transfer = self.internal_transfer(account_id.clone(), amount, &ft_token_id);
+
+        let callback_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "account_id": account_id,
+            "amount": U128(amount),
+            "ft_token_id": ft_token_id,
+        }))
+        .expect("Failed to serialize callback args");
+
+        transfer.then(Promise::new(env::current_account_id()).function_call(
+            "withdraw_callback".to_string(),
+            callback_args,
+            0,
+            Gas::ONE_TERA * 10,
+        ))
+    }
+
+    #[private]
+    fn withdraw_callback(
+        &mut self,
+        account_id: AccountId,
+        amount: U128,
+        ft_token_id: Option<AccountId>,
+    ) {
+        if near_sdk::is_promise_success() {
+            return;
+        }
+
+        //the transfer failed, the funds are claimable again
+        self.internal_credit(&account_id, amount.0, &ft_token_id);
+
+        // Emit NearEvent
+        NearEvent::balance_restore(BalanceRestoreData::new(
+            account_id.clone(),
+            amount,
+            ft_token_id,
+        ))
+        .actor(account_id)
+        .emit();
+    }
+}
+
+impl Contract {
+    /// Sends `amount` to `receiver_id`, in NEAR or through `ft_transfer` on the token.
+    fn internal_transfer(
+        &self,
+        receiver_id: AccountId,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) -> Promise {
+        match ft_token_id {
+            None => Promise::new(receiver_id).transfer(amount),
+            Some(ft_token_id) => {
+                let transfer_args: Vec<u8> =
+                    near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+                        "receiver_id": receiver_id,
+                        "amount": U128(amount),
+                    }))
+                    .unwrap();
+
+                Promise::new(ft_token_id.clone()).function_call(
+                    "ft_transfer".to_string(),
+                    transfer_args,
+                    ONE_YOCTO,
+                    Gas::ONE_TERA * 10,
+                )
+            }
+        }
+    }
+
+    /// Credits `amount` from the escrow to the claimable balance of `receiver_id`, in NEAR or in
+    /// the token. Settlements never transfer directly, the receiver pulls the funds with `withdraw`.
+    pub(crate) fn internal_payout(
+        &mut self,
+        receiver_id: AccountId,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) {
+        if amount == 0 {
+            return;
+        }
+        self.internal_escrow_withdraw(amount, ft_token_id);
+        self.internal_credit(&receiver_id, amount, ft_token_id);
+    }
+
+    /// Counts `amount` paid in by a buyer or a bidder as held in escrow.
+    pub(crate) fn internal_escrow_deposit(
+        &mut self,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) {
+        let total = self.escrow.get(ft_token_id) + amount;
+        self.escrow.set(ft_token_id, total);
+    }
+
+    /// Takes `amount` out of the escrow, once it is paid out or leaves the store.
+    pub(crate) fn internal_escrow_withdraw(
+        &mut self,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) {
+        let total = self.escrow.get(ft_token_id) - amount;
+        self.escrow.set(ft_token_id, total);
+    }
+
+    /// Adds `amount` to the claimable balance of `account_id`, the funds are already out of escrow.
+    fn internal_credit(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) {
+        let mut balance = self.balances.get(account_id).unwrap_or_default();
+        balance.set(ft_token_id, balance.get(ft_token_id) + amount);
+        self.balances.insert(account_id, &balance);
+        let total = self.claimable.get(ft_token_id) + amount;
+        self.claimable.set(ft_token_id, total);
+    }
+
+    fn internal_debit(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) {
+        let mut balance = self.balances.get(account_id).unwrap_or_default();
+        balance.set(ft_token_id, balance.get(ft_token_id) - amount);
+        let total = self.claimable.get(ft_token_id) - amount;
+        self.claimable.set(ft_token_id, total);
+
+        //empty balances are removed, the store only covers the bytes of funds waiting
+        if balance.is_empty() {
+            self.balances.remove(account_id);
+        } else {
+            self.balances.insert(account_id, &balance);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+/**
+ *  Collections
+ *
+ * Methods:
+ *
+ * - get_collection
+ *
+ * - collection_create
+ * - collection_update
+ * - collection_delete
+ * - collection_add_item
+ * - collection_remove_item
+ *
+ */
+use crate::*;
+
+// Collection metadata, ex. {"name": "Summer", "position": 1}
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionMetadata {
+    pub name: String,                // required, ex. "Summer"
+    pub description: Option<String>, // optional, ex. "Everything for the beach"
+    pub cover: Option<String>,       // optional, ex. "https://example.com/image.png"
+    #[serde(default)]
+    pub position: u32, // collections are listed by position, lowest first
+}
+
+// The Json Collection is what will be returned from view calls.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonCollection {
+    pub id: U64,
+    pub items: u64, // number of items in the collection
+    pub metadata: CollectionMetadata,
+}
+
+pub trait CollectionProvider {
+    fn get_collection(&self, collection_id: U64) -> Option<JsonCollection>;
+}
+
+#[near_bindgen]
+impl CollectionProvider for Contract {
+    fn get_collection(&self, collection_id: U64) -> Option<JsonCollection> {
+        let metadata = self.collections_by_id.get(&collection_id.into())?;
+        Some(JsonCollection {
+            id: collection_id,
+            items: self
+                .items_by_collection_id
+                .get(&collection_id.into())
+                .map_or(0, |item_ids| item_ids.len()),
+            metadata,
+        })
+    }
+}
+
+pub trait CollectionManager {
+    fn collection_create(&mut self, metadata: CollectionMetadata) -> U64;
+    fn collection_update(&mut self, collection_id: U64, metadata: CollectionMetadata);
+    fn collection_delete(&mut self, collection_id: U64);
+    fn collection_add_item(&mut self, collection_id: U64, item_id: U64);
+    fn collection_remove_item(&mut self, collection_id: U64, item_id: U64);
+}
+
+#[near_bindgen]
+impl CollectionManager for Contract {
+    #[payable]
+    fn collection_create(&mut self, metadata: CollectionMetadata) -> U64 {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can create a collection",
+        );
+        require!(!metadata.name.is_empty(), "Collection name can't be empty");
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        //ids are never reused, deleted collections leave a gap
+        let collection_id = self.next_collection_id;
+        self.next_collection_id += 1;
+        self.collections_by_id.insert(&collection_id, &metadata);
+        let position_key = (metadata.position, collection_id);
+        self.collections_by_position.insert(&position_key, &());
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::collection_create(CollectionData::new(U64(collection_id), metadata)).emit();
+
+        U64(collection_id)
+    }
+
+    #[payable]
+    fn collection_update(&mut self, collection_id: U64, metadata: CollectionMetadata) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can update a collection",
+        );
+        let collection_id = u64::from(collection_id);
+        let position = self
+            .collections_by_id
+            .get(&collection_id)
+            .expect("Collection does not exist")
+            .position;
+        require!(!metadata.name.is_empty(), "Collection name can't be empty");
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        self.collections_by_id.insert(&collection_id, &metadata);
+        //a new position moves the collection in the listing
+        let old_key = (position, collection_id);
+        let new_key = (metadata.position, collection_id);
+        if new_key != old_key {
+            self.collections_by_position.remove(&old_key);
+            self.collections_by_position.insert(&new_key, &());
+        }
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::collection_update(CollectionData::new(U64(collection_id), metadata)).emit();
+    }
+
+    fn collection_delete(&mut self, collection_id: U64) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can delete a collection",
+        );
+        let collection_id = u64::from(collection_id);
+        let position = self
+            .collections_by_id
+            .get(&collection_id)
+            .expect("Collection does not exist")
+            .position;
+
+        let initial_storage_usage = env::storage_usage();
+
+        //the items stay in the store, they only leave the collection
+        if let Some(mut item_ids) = self.items_by_collection_id.remove(&collection_id) {
+            for item_id in item_ids.to_vec() {
+                if let Some(mut item) = self.items_by_id.get(&item_id) {
+                    item.collections.retain(|id| *id != collection_id);
+                    self.items_by_id.insert(&item_id, &item);
+                }
+            }
+            item_ids.clear();
+        }
+        self.collections_by_id.remove(&collection_id);
+        let position_key = (position, collection_id);
+        self.collections_by_position.remove(&position_key);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::collection_delete(CollectionDeleteData::new(U64(collection_id))).emit();
+    }
+
+    #[payable]
+    fn collection_add_item(&mut self, collection_id: U64, item_id: U64) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can add an item to a collection",
+        );
+        require!(
+            self.collections_by_id.get(&collection_id.into()).is_some(),
+            "Collection does not exist"
+        );
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        require!(
+            !item.collections.contains(&collection_id.into()),
+            "Item is already in the collection"
+        );
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        //an item can be in several collections
+        item.collections.push(collection_id.into());
+        self.items_by_id.insert(&item_id.into(), &item);
+
+        let mut items = self
+            .items_by_collection_id
+            .get(&collection_id.into())
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::ItemsByCollectionIdInner {
+                        collection_id_hash: u64::from(collection_id).try_to_vec().unwrap(),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        items.insert(&item_id.into());
+        let collection_id = u64::from(collection_id);
+        self.items_by_collection_id.insert(&collection_id, &items);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::collection_add_item(CollectionItemData::new(U64(collection_id), item_id)).emit();
+    }
+
+    fn collection_remove_item(&mut self, collection_id: U64, item_id: U64) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can remove an item from a collection",
+        );
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        require!(
+            item.collections.contains(&collection_id.into()),
+            "Item is not in the collection"
+        );
+
+        let initial_storage_usage = env::storage_usage();
+
+        let collection_id = u64::from(collection_id);
+        item.collections.retain(|id| *id != collection_id);
+        self.items_by_id.insert(&item_id.into(), &item);
+        self.internal_collection_unindex(collection_id, item_id.into());
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::collection_remove_item(CollectionItemData::new(U64(collection_id), item_id))
+            .emit();
+    }
+}
+
+impl Contract {
+    /// Removes the item from the index of the collection, the item keeps its own list.
+    pub(crate) fn internal_collection_unindex(&mut self, collection_id: u64, item_id: u64) {
+        if let Some(mut items) = self.items_by_collection_id.get(&collection_id) {
+            items.remove(&item_id);
+            self.items_by_collection_id.insert(&collection_id, &items);
+        }
+    }
+}
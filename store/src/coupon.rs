@@ -0,0 +1,210 @@
+/**
+ *  Coupons
+ *
+ * Methods:
+ *
+ * - get_coupon
+ *
+ * - coupon_create
+ * - coupon_delete
+ *
+ */
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Discount {
+    Percent(u8), // ex. {"Percent": 10}, from 1 to 99
+    Fixed(U128), // ex. {"Fixed": "1000000000000000000000000"}, in NEAR
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum CouponScope {
+    Store,             // every item
+    Items(Vec<U64>),   // ex. {"Items": ["0", "3"]}
+    Tags(Vec<String>), // items with any of the tags, ex. {"Tags": ["music"]}
+}
+
+// Coupon
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Coupon {
+    pub code: String,                      // required, ex. "SUMMER10"
+    pub discount: Discount,                // required
+    pub scope: CouponScope,                // required
+    pub max_uses: Option<u64>,             // optional, for all buyers
+    pub max_uses_per_account: Option<u64>, // optional
+    pub starts_at: Option<u64>,            // optional, timestamp in ms
+    pub expires_at: Option<u64>,           // optional, timestamp in ms
+    // set by the contract
+    #[serde(default)]
+    pub uses: u64,
+}
+
+pub trait CouponProvider {
+    fn get_coupon(&self, code: String) -> Option<Coupon>;
+}
+
+#[near_bindgen]
+impl CouponProvider for Contract {
+    fn get_coupon(&self, code: String) -> Option<Coupon> {
+        self.coupons.get(&code)
+    }
+}
+
+pub trait CouponManager {
+    fn coupon_create(&mut self, coupon: Coupon);
+    fn coupon_delete(&mut self, code: String);
+}
+
+#[near_bindgen]
+impl CouponManager for Contract {
+    #[payable]
+    fn coupon_create(&mut self, coupon: Coupon) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can create a coupon"
+        );
+        require!(!coupon.code.is_empty(), "Coupon code can't be empty");
+        require!(
+            self.coupons.get(&coupon.code).is_none(),
+            "Coupon already exists"
+        );
+        match coupon.discount {
+            Discount::Percent(percent) => require!(
+                percent > 0 && percent < 100,
+                "Percent discount must be between 1 and 99"
+            ),
+            Discount::Fixed(amount) => {
+                require!(amount.0 > 0, "Fixed discount must be greater than 0")
+            }
+        }
+        if let (Some(starts_at), Some(expires_at)) = (coupon.starts_at, coupon.expires_at) {
+            require!(expires_at > starts_at, "Coupon must expire after it starts");
+        }
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        let coupon = Coupon { uses: 0, ..coupon };
+        self.coupons.insert(&coupon.code, &coupon);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::coupon_create(CouponCreateData::new(coupon)).emit();
+    }
+
+    fn coupon_delete(&mut self, code: String) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can delete a coupon"
+        );
+        require!(self.coupons.get(&code).is_some(), "Coupon does not exist");
+
+        let initial_storage_usage = env::storage_usage();
+        self.coupons.remove(&code);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::coupon_delete(CouponDeleteData::new(code)).emit();
+    }
+}
+
+impl Contract {
+    /// Checks that `buyer_id` can use the coupon on the item and returns the discount on `amount`.
+    pub(crate) fn internal_coupon_discount(
+        &self,
+        code: &str,
+        buyer_id: &AccountId,
+        item_id: u64,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) -> Balance {
+        let coupon = self
+            .coupons
+            .get(&code.to_string())
+            .expect("Coupon does not exist");
+
+        //check the coupon is running
+        let now = env::block_timestamp_ms();
+        if let Some(starts_at) = coupon.starts_at {
+            require!(now >= starts_at, "Coupon is not active yet");
+        }
+        if let Some(expires_at) = coupon.expires_at {
+            require!(now < expires_at, "Coupon has expired");
+        }
+
+        //check the usage caps
+        self.internal_check_coupon_uses(&coupon, buyer_id);
+
+        //check the item is in the coupon's scope
+        let in_scope = match &coupon.scope {
+            CouponScope::Store => true,
+            CouponScope::Items(item_ids) => item_ids.contains(&U64(item_id)),
+            CouponScope::Tags(tags) => self
+                .items_metadata_by_id
+                .get(&item_id)
+                .and_then(|metadata| metadata.tags)
+                .unwrap_or_default()
+                .iter()
+                .any(|tag| tags.contains(tag)),
+        };
+        require!(in_scope, "Coupon does not apply to this item");
+
+        let discount = match coupon.discount {
+            Discount::Percent(percent) => amount * percent as u128 / 100,
+            Discount::Fixed(discount) => {
+                //fixed amounts are in NEAR
+                require!(
+                    ft_token_id.is_none(),
+                    "Fixed discounts only apply to NEAR payments"
+                );
+                discount.0
+            }
+        };
+
+        //free orders would hold nothing in escrow for disputes and cancels
+        require!(discount < amount, "Coupon can't cover the whole price");
+        discount
+    }
+
+    /// Records a use of the coupon by `buyer_id`.
+    pub(crate) fn internal_use_coupon(&mut self, code: &str, buyer_id: &AccountId) {
+        let mut coupon = self
+            .coupons
+            .get(&code.to_string())
+            .expect("Coupon does not exist");
+        self.internal_check_coupon_uses(&coupon, buyer_id);
+
+        coupon.uses += 1;
+        self.coupons.insert(&coupon.code, &coupon);
+
+        let key = (coupon.code, buyer_id.clone());
+        let account_uses = self.coupon_uses_by_account.get(&key).unwrap_or(0);
+        self.coupon_uses_by_account
+            .insert(&key, &(account_uses + 1));
+    }
+
+    fn internal_check_coupon_uses(&self, coupon: &Coupon, buyer_id: &AccountId) {
+        if let Some(max_uses) = coupon.max_uses {
+            require!(coupon.uses < max_uses, "Coupon has reached its usage limit");
+        }
+        if let Some(max_uses_per_account) = coupon.max_uses_per_account {
+            let account_uses = self
+                .coupon_uses_by_account
+                .get(&(coupon.code.clone(), buyer_id.clone()))
+                .unwrap_or(0);
+            require!(
+                account_uses < max_uses_per_account,
+                "You have already used this coupon"
+            );
+        }
+    }
+}
@@ -4,6 +4,8 @@
  * Methods:
  *
  * - start_dispute
+ * - settlement_retry
+ * - settlement_cancel
  * - dispute_resolve
  *
  */
@@ -17,6 +19,18 @@ pub enum DisputeResolution {
     Draw,      // Split the funds
 }
 
+// Arbitration fee on its way to the arbiter, kept on the order until the dispute is created
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Settlement {
+    pub initiator_id: AccountId, // pays for the bytes, and for the fee of token orders
+    pub description: String,
+    pub fee: Balance,
+    pub new_amount: Balance,          // escrow left once the fee is paid
+    pub previous_status: OrderStatus, // restored if the settlement is cancelled
+    pub storage_bytes: StorageUsage,
+}
+
 pub trait DisputeManager {
     fn start_dispute(&mut self, order_id: U64, description: String) -> Promise;
     fn start_dispute_callback(
@@ -25,15 +39,18 @@ pub trait DisputeManager {
         new_amount: Balance,
         call_result: Result<String, PromiseError>,
     );
-    fn dispute_resolve(&mut self, order_id: U64, resolution: DisputeResolution) -> Promise;
+    fn settlement_retry(&mut self, order_id: U64) -> Promise;
+    fn settlement_cancel(&mut self, order_id: U64);
+    fn dispute_resolve(&mut self, order_id: U64, resolution: DisputeResolution);
 }
 
 #[near_bindgen]
 impl DisputeManager for Contract {
+    #[payable]
     fn start_dispute(&mut self, order_id: U64, description: String) -> Promise {
         //only buyer or owner can start dispute
 
-        let order = self.orders_by_id.get(&order_id.into()).unwrap();
+        let mut order = self.orders_by_id.get(&order_id.into()).unwrap();
         let owner_id = self.owner_id.clone();
 
         require!(
@@ -42,47 +59,63 @@ impl DisputeManager for Contract {
             "Only buyer or owner can start dispute"
         );
 
-        //only shipped orders can be disputed
+        //only shipped orders can be disputed, or returns the seller declined or didn't answer in time
+        //while the reserve is held, milestone orders can be disputed while in progress,
+        //only the unreleased amount is at stake
+        let disputed_return = order.status == OrderStatus::Completed
+            && order.reserve_until.is_some()
+            && match &order.return_request {
+                Some(request) => match request.status {
+                    ReturnStatus::Declined => true,
+                    ReturnStatus::Requested => env::block_timestamp_ms() >= request.respond_by,
+                    ReturnStatus::Accepted => false,
+                },
+                None => false,
+            };
         require!(
-            order.status == OrderStatus::Shipped,
-            "Only shipped orders can be disputed"
+            order.status == OrderStatus::Shipped
+                || (order.status == OrderStatus::Pending && !order.milestones.is_empty())
+                || (disputed_return && env::predecessor_account_id() == order.buyer_id),
+            "Only shipped orders, milestone orders or declined returns can be disputed"
         );
 
-        // 20% of the funds goes to the arbitrator contract
-        let arbitrator_amount = order.amount * 20 / 100;
-        let new_amount = order.amount - arbitrator_amount;
+        // 20% of the funds goes to the arbitrator contract,
+        // token orders can't pay it from escrow so the caller attaches it in NEAR
+        let (arbitrator_amount, new_amount) = if order.ft_token_id.is_none() {
+            require!(
+                env::attached_deposit() == 0,
+                "The arbitration fee of a NEAR order is paid from the escrow, attach no deposit"
+            );
+            let arbitrator_amount = order.amount * 20 / 100;
+            (arbitrator_amount, order.amount - arbitrator_amount)
+        } else {
+            require!(
+                env::attached_deposit() > 0,
+                "Attach the arbitration fee in NEAR to dispute a token order"
+            );
+            (env::attached_deposit(), order.amount)
+        };
 
-        let dispute_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
-            "store_id": env::current_account_id(),
-            "item_id": order.item_id.to_string(),
-            "order_id": order_id,
-            "buyer_id": order.buyer_id,
-            "seller_id": self.owner_id,
-            "description": description,
-        }))
-        .unwrap();
-
-        // cross contract call to arbitration contract (dispute contract)
-        let promise = Promise::new(self.arbiter_id.clone()).function_call(
-            "create_dispute".to_string(),
-            dispute_args,
-            arbitrator_amount,
-            Gas::ONE_TERA * 10,
-        );
-
-        // callback to store contract
-        let callback_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
-            "order_id": order_id,
-            "new_amount": new_amount,
-        }))
-        .expect("Failed to serialize callback args");
+        //the order is settling until the arbiter answers, the settlement is charged to the caller
+        let initial_storage_usage = env::storage_usage();
+        let mut settlement = Settlement {
+            initiator_id: env::predecessor_account_id(),
+            description,
+            fee: arbitrator_amount,
+            new_amount,
+            previous_status: order.status,
+            storage_bytes: 0,
+        };
+        order.status = OrderStatus::Settling;
+        self.open_disputes += 1;
+        order.settlement = Some(settlement.clone());
+        self.orders_by_id.insert(&order_id.into(), &order);
+        settlement.storage_bytes = env::storage_usage() - initial_storage_usage;
+        self.internal_storage_charge(&settlement.initiator_id, settlement.storage_bytes);
+        order.settlement = Some(settlement);
+        self.orders_by_id.insert(&order_id.into(), &order);
 
-        return promise.then(Promise::new(env::current_account_id()).function_call(
-            "start_dispute_callback".to_string(),
-            callback_args,
-            0,
-            Gas::ONE_TERA * 10,
-        ));
+        self.internal_settle(order_id, &order)
     }
 
     #[private]
@@ -92,25 +125,114 @@ impl DisputeManager for Contract {
         new_amount: Balance,
         #[callback_result] call_result: Result<String, PromiseError>,
     ) {
-        // Check if the promise succeeded
-        if call_result.is_err() || !near_sdk::is_promise_success() {
-            env::panic_str("Dispute creation failed..");
-        }
+        let mut order = self.orders_by_id.get(&order_id.into()).unwrap();
 
-        let dispute_id: u64 = near_sdk::serde_json::from_str(&call_result.unwrap()).unwrap();
+        // Check if the promise succeeded, the fee came back and can be sent again with settlement_retry
+        let dispute_id = match call_result {
+            Ok(result) if near_sdk::is_promise_success() => {
+                near_sdk::serde_json::from_str::<u64>(&result).ok()
+            }
+            _ => None,
+        };
+        let dispute_id = match dispute_id {
+            Some(dispute_id) => dispute_id,
+            None => {
+                order.status = OrderStatus::SettlementFailed;
+                self.orders_by_id.insert(&order_id.into(), &order);
 
-        let mut order = self.orders_by_id.get(&order_id.into()).unwrap();
+                //a token order's fee was attached apart, it is held until retried or cancelled
+                let settlement = order.settlement.as_ref().unwrap();
+                if order.ft_token_id.is_some() {
+                    self.internal_escrow_deposit(settlement.fee, &None);
+                }
+
+                // emit NearEvent, on behalf of the account that started the dispute
+                NearEvent::settlement_outcome(SettlementOutcomeData::new(order_id, false))
+                    .actor(settlement.initiator_id.clone())
+                    .emit();
+                return;
+            }
+        };
+
+        let settlement = order.settlement.take().unwrap();
         order.status = OrderStatus::Disputed;
-        order.amount = new_amount;
+        //the fee comes out of the milestones not released yet, it left the escrow with the arbiter
+        let fee = order.amount - new_amount;
+        order.deduct(fee);
+        self.internal_escrow_withdraw(fee, &order.ft_token_id);
         order.dispute_id = Some(dispute_id);
 
-        self.orders_by_id.insert(&order_id.into(), &order);        
+        self.orders_by_id.insert(&order_id.into(), &order);
+        self.internal_storage_release(&settlement.initiator_id, settlement.storage_bytes);
+
+        // emit NearEvent, on behalf of the account that started the dispute
+        NearEvent::settlement_outcome(SettlementOutcomeData::new(order_id, true))
+            .actor(settlement.initiator_id.clone())
+            .emit();
+        NearEvent::dispute_start(DisputeStartData::new(order_id))
+            .actor(settlement.initiator_id)
+            .emit();
+    }
+
+    fn settlement_retry(&mut self, order_id: U64) -> Promise {
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            env::predecessor_account_id() == order.buyer_id
+                || env::predecessor_account_id() == self.owner_id,
+            "Only buyer or owner can retry the settlement"
+        );
+        require!(
+            order.status == OrderStatus::SettlementFailed,
+            "Order settlement has not failed"
+        );
+
+        order.status = OrderStatus::Settling;
+        self.orders_by_id.insert(&order_id.into(), &order);
+
+        //a token order's fee is sent again from the escrow
+        if order.ft_token_id.is_some() {
+            let fee = order.settlement.as_ref().unwrap().fee;
+            self.internal_escrow_withdraw(fee, &None);
+        }
+
+        self.internal_settle(order_id, &order)
+    }
+
+    fn settlement_cancel(&mut self, order_id: U64) {
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            order.status == OrderStatus::SettlementFailed,
+            "Order settlement has not failed"
+        );
+        let settlement = order.settlement.take().unwrap();
+        require!(
+            env::predecessor_account_id() == settlement.initiator_id,
+            "Only the account that started the dispute can cancel it"
+        );
+
+        //the order goes back to where it was, a token order's fee is the caller's again
+        order.status = settlement.previous_status;
+        self.open_disputes -= 1;
+        self.orders_by_id.insert(&order_id.into(), &order);
+        self.internal_storage_release(&settlement.initiator_id, settlement.storage_bytes);
+        if order.ft_token_id.is_some() {
+            self.internal_payout(settlement.initiator_id, settlement.fee, &None);
+        }
 
         // emit NearEvent
-        NearEvent::dispute_start(DisputeStartData::new(order_id)).emit();
+        NearEvent::settlement_cancel(SettlementCancelData::new(order_id)).emit();
+
+        //a proposed arbiter may have been waiting for this order
+        self.internal_apply_pending_arbiter();
     }
 
-    fn dispute_resolve(&mut self, order_id: U64, resolution: DisputeResolution) -> Promise {
+    fn dispute_resolve(&mut self, order_id: U64, resolution: DisputeResolution) {
         let arbiter_id = self.arbiter_id.clone();
 
         //only arbiter can resolve dispute
@@ -132,28 +254,96 @@ impl DisputeManager for Contract {
             "Only disputed orders can be resolved"
         );
 
-        let seller_id = self.owner_id.clone();
         let buyer_id = order.buyer_id.clone();
 
-        //set order status to resolved
-        self.orders_by_id.insert(
-            &order_id.into(),
-            &Order {
-                status: OrderStatus::Resolved,
-                ..order
-            },
-        );
+        //set order status to resolved, a disputed return's reserve is paid out below
+        let order = Order {
+            status: OrderStatus::Resolved,
+            reserve_until: None,
+            ..order
+        };
+        self.orders_by_id.insert(&order_id.into(), &order);
+        self.open_disputes -= 1;
+
+        //the order is kept for history but no longer charged to the buyer
+        self.internal_storage_release(&order.buyer_id, order.storage_bytes);
+
+        //the buyer gets refunded so the units go back to the item
+        if resolution == DisputeResolution::BuyerWon {
+            self.internal_return_stock(order.item_id, &order.variant, order.quantity);
+        }
 
         // emit NearEvent
         NearEvent::dispute_resolve(DisputeResolveData::new(order_id, resolution.clone())).emit();
 
-        //transfer funds
+        //credit funds
         match resolution {
-            DisputeResolution::BuyerWon => Promise::new(buyer_id).transfer(order.amount),
-            DisputeResolution::SellerWon => Promise::new(seller_id).transfer(order.amount),
-            DisputeResolution::Draw => Promise::new(buyer_id)
-                .transfer(order.amount / 2)
-                .and(Promise::new(seller_id).transfer(order.amount / 2)),
+            DisputeResolution::BuyerWon => {
+                self.internal_payout(buyer_id, order.amount, &order.ft_token_id)
+            }
+            DisputeResolution::SellerWon => {
+                self.internal_payout_seller(order_id.into(), order.amount, &order.ft_token_id)
+            }
+            DisputeResolution::Draw => {
+                //the seller's half gets the odd yocto, nothing is left in escrow
+                let buyer_half = order.amount / 2;
+                self.internal_payout(buyer_id, buyer_half, &order.ft_token_id);
+                self.internal_payout_seller(
+                    order_id.into(),
+                    order.amount - buyer_half,
+                    &order.ft_token_id,
+                );
+            }
         }
+
+        //a proposed arbiter may have been waiting for this dispute
+        self.internal_apply_pending_arbiter();
+    }
+}
+
+impl Contract {
+    /// Sends the arbitration fee with the dispute to the arbiter, the callback settles the order.
+    pub(crate) fn internal_settle(&self, order_id: U64, order: &Order) -> Promise {
+        let settlement = order.settlement.as_ref().unwrap();
+
+        let dispute_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "store_id": env::current_account_id(),
+            "item_id": order.item_id.to_string(),
+            "order_id": order_id,
+            "buyer_id": order.buyer_id,
+            "seller_id": self.owner_id,
+            "description": settlement.description,
+        }))
+        .unwrap();
+
+        // emit NearEvent
+        NearEvent::settlement_attempt(SettlementAttemptData::new(
+            order_id,
+            self.arbiter_id.clone(),
+            U128(settlement.fee),
+        ))
+        .emit();
+
+        // cross contract call to arbitration contract (dispute contract)
+        let promise = Promise::new(self.arbiter_id.clone()).function_call(
+            "create_dispute".to_string(),
+            dispute_args,
+            settlement.fee,
+            Gas::ONE_TERA * 10,
+        );
+
+        // callback to store contract
+        let callback_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "order_id": order_id,
+            "new_amount": settlement.new_amount,
+        }))
+        .expect("Failed to serialize callback args");
+
+        promise.then(Promise::new(env::current_account_id()).function_call(
+            "start_dispute_callback".to_string(),
+            callback_args,
+            0,
+            Gas::ONE_TERA * 10,
+        ))
     }
 }
@@ -5,6 +5,10 @@
  * Methods:
  *
  * - get_items
+ * - get_items_for_collection
+ * - get_collections
+ * - get_items_by_tag
+ * - get_tags
  *
  * - get_orders
  * - get_orders_for_item
@@ -14,11 +18,29 @@
  * - get_reviews_for_item
  * - get_reviews_for_buyer
  *
+ * - get_coupons
+ *
+ * - get_staff
+ *
  */
 use crate::*;
 
 pub trait Enumeration {
     fn get_items(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonItem>;
+    fn get_items_for_collection(
+        &self,
+        collection_id: U64,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<JsonItem>;
+    fn get_collections(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonCollection>;
+    fn get_items_by_tag(
+        &self,
+        tag: String,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<JsonItem>;
+    fn get_tags(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonTag>;
     fn get_orders(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<Order>;
     fn get_orders_for_item(
         &self,
@@ -45,6 +67,8 @@ pub trait Enumeration {
         from_index: Option<U64>,
         limit: Option<U64>,
     ) -> Vec<Review>;
+    fn get_coupons(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<Coupon>;
+    fn get_staff(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonStaff>;
 }
 
 #[near_bindgen]
@@ -66,6 +90,88 @@ impl Enumeration for Contract {
             .collect()
     }
 
+    fn get_items_for_collection(
+        &self,
+        collection_id: U64,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<JsonItem> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = from_index.unwrap_or(U64(0)).0;
+
+        //collections without items have no index yet
+        let item_ids = match self.items_by_collection_id.get(&collection_id.into()) {
+            Some(item_ids) => item_ids,
+            None => return vec![],
+        };
+
+        item_ids
+            .iter()
+            //skip to the index we specified in the start variable
+            .skip(start as usize)
+            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+            .take(limit.unwrap_or(U64(50)).0 as usize)
+            .map(|item_id| self.get_item(U64(item_id)).unwrap())
+            .collect()
+    }
+
+    fn get_collections(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonCollection> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = from_index.unwrap_or(U64(0)).0;
+
+        //collections are listed by position, then by creation
+        self.collections_by_position
+            .iter()
+            //skip to the index we specified in the start variable
+            .skip(start as usize)
+            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+            .take(limit.unwrap_or(U64(50)).0 as usize)
+            .map(|((_, collection_id), _)| self.get_collection(U64(collection_id)).unwrap())
+            .collect()
+    }
+
+    fn get_items_by_tag(
+        &self,
+        tag: String,
+        from_index: Option<U64>,
+        limit: Option<U64>,
+    ) -> Vec<JsonItem> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = from_index.unwrap_or(U64(0)).0;
+
+        //tags no item uses aren't indexed
+        let item_ids = match self.items_by_tag.get(&tag) {
+            Some(item_ids) => item_ids,
+            None => return vec![],
+        };
+
+        item_ids
+            .iter()
+            //skip to the index we specified in the start variable
+            .skip(start as usize)
+            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+            .take(limit.unwrap_or(U64(50)).0 as usize)
+            .map(|item_id| self.get_item(U64(item_id)).unwrap())
+            .collect()
+    }
+
+    fn get_tags(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonTag> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = from_index.unwrap_or(U64(0)).0;
+
+        self.items_by_tag
+            .iter()
+            //skip to the index we specified in the start variable
+            .skip(start as usize)
+            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+            .take(limit.unwrap_or(U64(50)).0 as usize)
+            .map(|(tag, item_ids)| JsonTag {
+                tag,
+                items: item_ids.len(),
+            })
+            .collect()
+    }
+
     fn get_orders(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<Order> {
         //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
         let start = from_index.unwrap_or(U64(0)).0;
@@ -195,4 +301,31 @@ impl Enumeration for Contract {
             //since we turned the keys into an iterator, we need to turn it back into a vector to return
             .collect()
     }
+
+    fn get_coupons(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<Coupon> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = from_index.unwrap_or(U64(0)).0;
+
+        self.coupons
+            .values()
+            //skip to the index we specified in the start variable
+            .skip(start as usize)
+            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+            .take(limit.unwrap_or(U64(50)).0 as usize)
+            .collect()
+    }
+
+    fn get_staff(&self, from_index: Option<U64>, limit: Option<U64>) -> Vec<JsonStaff> {
+        //where to start pagination - if we have a from_index, we'll use that - otherwise start from 0 index
+        let start = from_index.unwrap_or(U64(0)).0;
+
+        self.staff
+            .iter()
+            //skip to the index we specified in the start variable
+            .skip(start as usize)
+            //take the first "limit" elements in the vector. If we didn't specify a limit, use 50
+            .take(limit.unwrap_or(U64(50)).0 as usize)
+            .map(|(account_id, role)| JsonStaff { account_id, role })
+            .collect()
+    }
 }
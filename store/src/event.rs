@@ -15,6 +15,7 @@ pub enum NearEvent {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Nep297Event {
     pub version: &'static str,
+    pub actor_id: AccountId, // account that performed the action, the owner or staff for store actions
     #[serde(flatten)]
     pub event_kind: Nep297EventKind,
 }
@@ -28,20 +29,73 @@ pub enum Nep297EventKind {
     StoreCreate(StoreCreateData),
     StoreUpdate(StoreUpdateData),
     StoreDelete(),
+    StoreConfirmationWindowUpdate(ConfirmationWindowUpdateData),
+    StoreHandlingTimeUpdate(HandlingTimeUpdateData),
+    StoreReturnWindowUpdate(ReturnWindowUpdateData),
+    StoreArbiterPropose(ArbiterProposeData),
+    StoreArbiterCancel(),
+    StorePause(StorePauseData),
+    StoreResume(),
+    // staff
+    StaffGrant(StaffGrantData),
+    StaffRevoke(StaffRevokeData),
+    // ownership
+    OwnershipPropose(OwnershipProposeData),
+    OwnershipCancel(),
+    OwnershipTransfer(OwnershipTransferData),
     // item
     ItemCreate(ItemCreateData),
     ItemUpdate(ItemUpdateData),
     ItemDelete(ItemDeleteData),
+    ItemStockUpdate(ItemStockUpdateData),
+    ItemMilestonesUpdate(ItemMilestonesUpdateData),
+    ItemStatusUpdate(ItemStatusUpdateData),
+    // collection
+    CollectionCreate(CollectionData),
+    CollectionUpdate(CollectionData),
+    CollectionDelete(CollectionDeleteData),
+    CollectionAddItem(CollectionItemData),
+    CollectionRemoveItem(CollectionItemData),
     // order
     ItemBuy(ItemBuyData),
     OrderShipped(OrderShippedData),
     OrderComplete(OrderCompleteData),
     OrderCancel(OrderCancelData),
+    OrderLateRefund(OrderLateRefundData),
+    OrderRefund(OrderRefundData),
+    OrderCommission(OrderCommissionData),
+    MilestoneApprove(MilestoneApproveData),
+    CartCheckout(CartCheckoutData),
+    // balance
+    BalanceWithdraw(BalanceWithdrawData),
+    BalanceRestore(BalanceRestoreData),
     // dispute
     DisputeStart(DisputeStartData),
+    SettlementAttempt(SettlementAttemptData),
+    SettlementOutcome(SettlementOutcomeData),
+    SettlementCancel(SettlementCancelData),
     DisputeResolve(DisputeResolveData),
     // review
     ReviewCreate(ReviewCreateData),
+    // coupon
+    CouponCreate(CouponCreateData),
+    CouponDelete(CouponDeleteData),
+    // auction
+    AuctionStart(AuctionStartData),
+    AuctionBid(AuctionBidData),
+    AuctionSettle(AuctionSettleData),
+    // offer
+    OfferMake(OfferMakeData),
+    OfferCounter(OfferCounterData),
+    OfferAccept(OfferAcceptData),
+    OfferReject(OfferRejectData),
+    OfferWithdraw(OfferWithdrawData),
+    OfferExpire(OfferExpireData),
+    // return
+    ReturnRequest(ReturnRequestData),
+    ReturnAccept(ReturnAcceptData),
+    ReturnDecline(ReturnDeclineData),
+    ReturnReserveRelease(ReturnReserveReleaseData),
 }
 
 /**
@@ -53,10 +107,42 @@ pub enum Nep297EventKind {
 pub struct StoreCreateData {
     pub owner_id: AccountId,
     pub arbiter_id: AccountId,
+    pub treasury_id: AccountId,
+    pub commission_bps: u16,
     pub metadata: StoreMetadata,
 }
 
 impl StoreCreateData {
+    pub fn new(
+        owner_id: AccountId,
+        arbiter_id: AccountId,
+        treasury_id: AccountId,
+        commission_bps: u16,
+        metadata: StoreMetadata,
+    ) -> Self {
+        Self {
+            owner_id: owner_id,
+            arbiter_id: arbiter_id,
+            treasury_id,
+            commission_bps,
+            metadata,
+        }
+    }
+}
+
+/**
+ * event: store_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StoreUpdateData {
+    pub owner_id: AccountId,
+    pub arbiter_id: AccountId,
+    pub metadata: StoreMetadata,
+}
+
+impl StoreUpdateData {
     pub fn new(owner_id: AccountId, arbiter_id: AccountId, metadata: StoreMetadata) -> Self {
         Self {
             owner_id: owner_id,
@@ -67,239 +153,1099 @@ impl StoreCreateData {
 }
 
 /**
- * event: store_update
+ * event: store_confirmation_window_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConfirmationWindowUpdateData {
+    pub confirmation_window: u64,
+}
+
+impl ConfirmationWindowUpdateData {
+    pub fn new(confirmation_window: u64) -> Self {
+        Self {
+            confirmation_window,
+        }
+    }
+}
+
+/**
+ * event: store_handling_time_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HandlingTimeUpdateData {
+    pub handling_time: u64,
+}
+
+impl HandlingTimeUpdateData {
+    pub fn new(handling_time: u64) -> Self {
+        Self { handling_time }
+    }
+}
+
+/**
+ * event: store_return_window_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReturnWindowUpdateData {
+    pub return_window: u64,
+}
+
+impl ReturnWindowUpdateData {
+    pub fn new(return_window: u64) -> Self {
+        Self { return_window }
+    }
+}
+
+/**
+ * event: store_pause
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StorePauseData {
+    pub reopens_at: Option<u64>,
+}
+
+impl StorePauseData {
+    pub fn new(reopens_at: Option<u64>) -> Self {
+        Self { reopens_at }
+    }
+}
+
+/**
+ * event: store_arbiter_propose
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ArbiterProposeData {
+    pub arbiter_id: AccountId,
+}
+
+impl ArbiterProposeData {
+    pub fn new(arbiter_id: AccountId) -> Self {
+        Self { arbiter_id }
+    }
+}
+
+/**
+ * event: staff_grant
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StaffGrantData {
+    pub account_id: AccountId,
+    pub role: StaffRole,
+}
+
+impl StaffGrantData {
+    pub fn new(account_id: AccountId, role: StaffRole) -> Self {
+        Self { account_id, role }
+    }
+}
+
+/**
+ * event: staff_revoke
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StaffRevokeData {
+    pub account_id: AccountId,
+}
+
+impl StaffRevokeData {
+    pub fn new(account_id: AccountId) -> Self {
+        Self { account_id }
+    }
+}
+
+/**
+ * event: ownership_propose
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OwnershipProposeData {
+    pub owner_id: AccountId,
+    pub new_owner_id: AccountId,
+}
+
+impl OwnershipProposeData {
+    pub fn new(owner_id: AccountId, new_owner_id: AccountId) -> Self {
+        Self {
+            owner_id,
+            new_owner_id,
+        }
+    }
+}
+
+/**
+ * event: ownership_transfer
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OwnershipTransferData {
+    pub old_owner_id: AccountId,
+    pub new_owner_id: AccountId,
+}
+
+impl OwnershipTransferData {
+    pub fn new(old_owner_id: AccountId, new_owner_id: AccountId) -> Self {
+        Self {
+            old_owner_id,
+            new_owner_id,
+        }
+    }
+}
+
+/**
+ * event: item_create
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemCreateData {
+    pub item_id: U64,
+    pub price: U128,
+    pub ft_prices: HashMap<AccountId, U128>,
+    pub stock: u64,
+    pub variants: HashMap<String, JsonItemVariant>,
+    pub status: ItemStatus,
+    pub metadata: ItemMetadata,
+}
+
+impl ItemCreateData {
+    pub fn new(
+        item_id: U64,
+        price: U128,
+        ft_prices: HashMap<AccountId, U128>,
+        stock: u64,
+        variants: HashMap<String, JsonItemVariant>,
+        status: ItemStatus,
+        metadata: ItemMetadata,
+    ) -> Self {
+        Self {
+            item_id: item_id,
+            price: price,
+            ft_prices,
+            stock,
+            variants,
+            status: status,
+            metadata,
+        }
+    }
+}
+
+/**
+ * event: item_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemUpdateData {
+    pub item_id: U64,
+    pub price: U128,
+    pub ft_prices: HashMap<AccountId, U128>,
+    pub stock: u64,
+    pub variants: HashMap<String, JsonItemVariant>,
+    pub status: ItemStatus,
+    pub metadata: ItemMetadata,
+}
+
+impl ItemUpdateData {
+    pub fn new(
+        item_id: U64,
+        price: U128,
+        ft_prices: HashMap<AccountId, U128>,
+        stock: u64,
+        variants: HashMap<String, JsonItemVariant>,
+        status: ItemStatus,
+        metadata: ItemMetadata,
+    ) -> Self {
+        Self {
+            item_id: item_id,
+            price: price,
+            ft_prices,
+            stock,
+            variants,
+            status: status,
+            metadata,
+        }
+    }
+}
+
+/**
+ * event: item_delete
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemDeleteData {
+    pub item_id: U64,
+}
+
+impl ItemDeleteData {
+    pub fn new(item_id: U64) -> Self {
+        Self { item_id: item_id }
+    }
+}
+
+/**
+ * event: item_stock_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemStockUpdateData {
+    pub item_id: U64,
+    pub variant: Option<String>,
+    pub variant_stock: Option<u64>,
+    pub stock: u64,
+    pub status: ItemStatus,
+}
+
+impl ItemStockUpdateData {
+    pub fn new(
+        item_id: U64,
+        variant: Option<String>,
+        variant_stock: Option<u64>,
+        stock: u64,
+        status: ItemStatus,
+    ) -> Self {
+        Self {
+            item_id,
+            variant,
+            variant_stock,
+            stock,
+            status,
+        }
+    }
+}
+
+/**
+ * event: item_status_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemStatusUpdateData {
+    pub item_id: U64,
+    pub status: ItemStatus,
+}
+
+impl ItemStatusUpdateData {
+    pub fn new(item_id: U64, status: ItemStatus) -> Self {
+        Self { item_id, status }
+    }
+}
+
+/**
+ * event: collection_create, collection_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollectionData {
+    pub collection_id: U64,
+    pub metadata: CollectionMetadata,
+}
+
+impl CollectionData {
+    pub fn new(collection_id: U64, metadata: CollectionMetadata) -> Self {
+        Self {
+            collection_id,
+            metadata,
+        }
+    }
+}
+
+/**
+ * event: collection_delete
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollectionDeleteData {
+    pub collection_id: U64,
+}
+
+impl CollectionDeleteData {
+    pub fn new(collection_id: U64) -> Self {
+        Self { collection_id }
+    }
+}
+
+/**
+ * event: collection_add_item, collection_remove_item
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CollectionItemData {
+    pub collection_id: U64,
+    pub item_id: U64,
+}
+
+impl CollectionItemData {
+    pub fn new(collection_id: U64, item_id: U64) -> Self {
+        Self {
+            collection_id,
+            item_id,
+        }
+    }
+}
+
+/**
+ * event: item_milestones_update
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemMilestonesUpdateData {
+    pub item_id: U64,
+    pub milestones: Vec<ItemMilestone>,
+}
+
+impl ItemMilestonesUpdateData {
+    pub fn new(item_id: U64, milestones: Vec<ItemMilestone>) -> Self {
+        Self {
+            item_id,
+            milestones,
+        }
+    }
+}
+
+/**
+ * event: item_buy
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ItemBuyData {
+    pub item_id: U64,
+    pub buyer_id: AccountId,
+    pub order_id: U64,
+    pub variant: Option<String>,
+    pub quantity: u64,
+    pub price: U128,
+    pub ft_token_id: Option<AccountId>,
+    pub coupon: Option<String>,
+}
+
+impl ItemBuyData {
+    pub fn new(order_id: U64, order: Order) -> Self {
+        Self {
+            item_id: U64(order.item_id),
+            buyer_id: order.buyer_id,
+            order_id: order_id,
+            variant: order.variant,
+            quantity: order.quantity,
+            price: U128(order.amount),
+            ft_token_id: order.ft_token_id,
+            coupon: order.coupon,
+        }
+    }
+}
+
+/**
+* event: order_shipped
+*/
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderShippedData {
+    pub order_id: U64,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub expected_delivery: Option<u64>,
+}
+
+impl OrderShippedData {
+    pub fn new(order_id: U64, shipping: ShippingDetails) -> Self {
+        Self {
+            order_id,
+            carrier: shipping.carrier,
+            tracking_number: shipping.tracking_number,
+            expected_delivery: shipping.expected_delivery,
+        }
+    }
+}
+
+/**
+ * event: order_complete
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderCompleteData {
+    pub order_id: U64,
+}
+
+impl OrderCompleteData {
+    pub fn new(order_id: U64) -> Self {
+        Self { order_id: order_id }
+    }
+}
+
+/**
+ * event: order_cancel
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderCancelData {
+    pub order_id: U64,
+}
+
+impl OrderCancelData {
+    pub fn new(order_id: U64) -> Self {
+        Self { order_id: order_id }
+    }
+}
+
+/**
+ * event: order_late_refund
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderLateRefundData {
+    pub order_id: U64,
+    pub buyer_id: AccountId,
+    pub amount: U128,
+}
+
+impl OrderLateRefundData {
+    pub fn new(order_id: U64, buyer_id: AccountId, amount: U128) -> Self {
+        Self {
+            order_id,
+            buyer_id,
+            amount,
+        }
+    }
+}
+
+/**
+ * event: order_refund
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderRefundData {
+    pub order_id: U64,
+    pub amount: U128,
+    pub remaining: U128,
+    pub reason: Option<String>,
+}
+
+impl OrderRefundData {
+    pub fn new(order_id: U64, amount: U128, remaining: U128, reason: Option<String>) -> Self {
+        Self {
+            order_id,
+            amount,
+            remaining,
+            reason,
+        }
+    }
+}
+
+/**
+ * event: order_commission
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderCommissionData {
+    pub order_id: U64,
+    pub treasury_id: AccountId,
+    pub amount: U128,
+    pub ft_token_id: Option<AccountId>,
+}
+
+impl OrderCommissionData {
+    pub fn new(
+        order_id: U64,
+        treasury_id: AccountId,
+        amount: U128,
+        ft_token_id: Option<AccountId>,
+    ) -> Self {
+        Self {
+            order_id,
+            treasury_id,
+            amount,
+            ft_token_id,
+        }
+    }
+}
+
+/**
+ * event: milestone_approve
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MilestoneApproveData {
+    pub order_id: U64,
+    pub milestone: u32,
+    pub amount: U128,
+}
+
+impl MilestoneApproveData {
+    pub fn new(order_id: U64, milestone: u32, amount: U128) -> Self {
+        Self {
+            order_id,
+            milestone,
+            amount,
+        }
+    }
+}
+
+/**
+ * event: cart_checkout
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CartCheckoutData {
+    pub cart_id: U64,
+    pub buyer_id: AccountId,
+    pub order_ids: Vec<U64>,
+    pub amount: U128,
+    pub refund: U128,
+}
+
+impl CartCheckoutData {
+    pub fn new(
+        cart_id: U64,
+        buyer_id: AccountId,
+        order_ids: Vec<U64>,
+        amount: U128,
+        refund: U128,
+    ) -> Self {
+        Self {
+            cart_id,
+            buyer_id,
+            order_ids,
+            amount,
+            refund,
+        }
+    }
+}
+
+/**
+ * event: balance_withdraw
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceWithdrawData {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub ft_token_id: Option<AccountId>,
+}
+
+impl BalanceWithdrawData {
+    pub fn new(account_id: AccountId, amount: U128, ft_token_id: Option<AccountId>) -> Self {
+        Self {
+            account_id,
+            amount,
+            ft_token_id,
+        }
+    }
+}
+
+/**
+ * event: balance_restore
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BalanceRestoreData {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub ft_token_id: Option<AccountId>,
+}
+
+impl BalanceRestoreData {
+    pub fn new(account_id: AccountId, amount: U128, ft_token_id: Option<AccountId>) -> Self {
+        Self {
+            account_id,
+            amount,
+            ft_token_id,
+        }
+    }
+}
+
+/**
+ * event: dispute_start
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DisputeStartData {
+    pub order_id: U64,
+}
+
+impl DisputeStartData {
+    pub fn new(order_id: U64) -> Self {
+        Self { order_id: order_id }
+    }
+}
+
+/**
+ * event: settlement_attempt
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SettlementAttemptData {
+    pub order_id: U64,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+}
+
+impl SettlementAttemptData {
+    pub fn new(order_id: U64, receiver_id: AccountId, amount: U128) -> Self {
+        Self {
+            order_id,
+            receiver_id,
+            amount,
+        }
+    }
+}
+
+/**
+ * event: settlement_outcome
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SettlementOutcomeData {
+    pub order_id: U64,
+    pub success: bool,
+}
+
+impl SettlementOutcomeData {
+    pub fn new(order_id: U64, success: bool) -> Self {
+        Self { order_id, success }
+    }
+}
+
+/**
+ * event: settlement_cancel
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SettlementCancelData {
+    pub order_id: U64,
+}
+
+impl SettlementCancelData {
+    pub fn new(order_id: U64) -> Self {
+        Self { order_id }
+    }
+}
+
+/**
+ * event: dispute_resolve
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DisputeResolveData {
+    pub order_id: U64,
+    pub resolution: DisputeResolution,
+}
+
+impl DisputeResolveData {
+    pub fn new(order_id: U64, resolution: DisputeResolution) -> Self {
+        Self {
+            order_id: order_id,
+            resolution: resolution,
+        }
+    }
+}
+
+/**
+ * event: review_create
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReviewCreateData {
+    pub item_id: U64,
+    pub review_id: U64,
+    pub reviewer_id: AccountId,
+    pub rating: u8,
+    pub comment: String,
+}
+
+impl ReviewCreateData {
+    pub fn new(
+        item_id: U64,
+        review_id: U64,
+        reviewer_id: AccountId,
+        rating: u8,
+        comment: String,
+    ) -> Self {
+        Self {
+            item_id: item_id,
+            review_id: review_id,
+            reviewer_id: reviewer_id,
+            rating: rating,
+            comment,
+        }
+    }
+}
+
+/**
+ * event: coupon_create
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CouponCreateData {
+    pub coupon: Coupon,
+}
+
+impl CouponCreateData {
+    pub fn new(coupon: Coupon) -> Self {
+        Self { coupon }
+    }
+}
+
+/**
+ * event: coupon_delete
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct StoreUpdateData {
-    pub owner_id: AccountId,
-    pub arbiter_id: AccountId,
-    pub metadata: StoreMetadata,
+pub struct CouponDeleteData {
+    pub code: String,
 }
 
-impl StoreUpdateData {
-    pub fn new(owner_id: AccountId, arbiter_id: AccountId, metadata: StoreMetadata) -> Self {
-        Self {
-            owner_id: owner_id,
-            arbiter_id: arbiter_id,
-            metadata,
-        }
+impl CouponDeleteData {
+    pub fn new(code: String) -> Self {
+        Self { code }
     }
 }
 
 /**
- * event: item_create
+ * event: auction_start
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ItemCreateData {
+pub struct AuctionStartData {
     pub item_id: U64,
-    pub price: U128,
-    pub status: ItemStatus,
-    pub metadata: ItemMetadata,
+    pub reserve_price: U128,
+    pub min_increment: U128,
+    pub ends_at: u64,
+    pub extension: u64,
 }
 
-impl ItemCreateData {
-    pub fn new(item_id: U64, price: U128, status: ItemStatus, metadata: ItemMetadata) -> Self {
+impl AuctionStartData {
+    pub fn new(
+        item_id: U64,
+        reserve_price: U128,
+        min_increment: U128,
+        ends_at: u64,
+        extension: u64,
+    ) -> Self {
         Self {
-            item_id: item_id,
-            price: price,
-            status: status,
-            metadata,
+            item_id,
+            reserve_price,
+            min_increment,
+            ends_at,
+            extension,
         }
     }
 }
 
 /**
- * event: item_update
+ * event: auction_bid
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ItemUpdateData {
+pub struct AuctionBidData {
     pub item_id: U64,
-    pub price: U128,
-    pub status: ItemStatus,
-    pub metadata: ItemMetadata,
+    pub bidder_id: AccountId,
+    pub amount: U128,
+    pub ends_at: u64,
 }
 
-impl ItemUpdateData {
-    pub fn new(item_id: U64, price: U128, status: ItemStatus, metadata: ItemMetadata) -> Self {
+impl AuctionBidData {
+    pub fn new(item_id: U64, bidder_id: AccountId, amount: U128, ends_at: u64) -> Self {
         Self {
-            item_id: item_id,
-            price: price,
-            status: status,
-            metadata,
+            item_id,
+            bidder_id,
+            amount,
+            ends_at,
         }
     }
 }
 
 /**
- * event: item_delete
+ * event: auction_settle
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ItemDeleteData {
+pub struct AuctionSettleData {
     pub item_id: U64,
+    pub winner_id: Option<AccountId>,
+    pub amount: Option<U128>,
+    pub order_id: Option<U64>,
 }
 
-impl ItemDeleteData {
-    pub fn new(item_id: U64) -> Self {
-        Self { item_id: item_id }
+impl AuctionSettleData {
+    pub fn new(
+        item_id: U64,
+        winner_id: Option<AccountId>,
+        amount: Option<U128>,
+        order_id: Option<U64>,
+    ) -> Self {
+        Self {
+            item_id,
+            winner_id,
+            amount,
+            order_id,
+        }
     }
 }
 
 /**
- * event: item_buy
+ * event: offer_make
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ItemBuyData {
+pub struct OfferMakeData {
+    pub offer_id: U64,
     pub item_id: U64,
     pub buyer_id: AccountId,
-    pub order_id: U64,
-    pub price: U128,
+    pub amount: U128,
+    pub expires_at: u64,
 }
 
-impl ItemBuyData {
-    pub fn new(item_id: U64, buyer_id: AccountId, price: U128, order_id: U64) -> Self {
+impl OfferMakeData {
+    pub fn new(
+        offer_id: U64,
+        item_id: U64,
+        buyer_id: AccountId,
+        amount: U128,
+        expires_at: u64,
+    ) -> Self {
         Self {
-            item_id: item_id,
-            buyer_id: buyer_id,
-            order_id: order_id,
-            price: price,
+            offer_id,
+            item_id,
+            buyer_id,
+            amount,
+            expires_at,
         }
     }
 }
 
 /**
-* event: order_shipped
-*/
+ * event: offer_counter
+ */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct OrderShippedData {
-    pub order_id: U64,
+pub struct OfferCounterData {
+    pub offer_id: U64,
+    pub amount: U128,
+    pub expires_at: u64,
 }
 
-impl OrderShippedData {
-    pub fn new(order_id: U64) -> Self {
-        Self { order_id: order_id }
+impl OfferCounterData {
+    pub fn new(offer_id: U64, amount: U128, expires_at: u64) -> Self {
+        Self {
+            offer_id,
+            amount,
+            expires_at,
+        }
     }
 }
 
 /**
- * event: order_complete
+ * event: offer_accept
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct OrderCompleteData {
+pub struct OfferAcceptData {
+    pub offer_id: U64,
     pub order_id: U64,
+    pub amount: U128,
 }
 
-impl OrderCompleteData {
-    pub fn new(order_id: U64) -> Self {
-        Self { order_id: order_id }
+impl OfferAcceptData {
+    pub fn new(offer_id: U64, order_id: U64, amount: U128) -> Self {
+        Self {
+            offer_id,
+            order_id,
+            amount,
+        }
     }
 }
 
 /**
- * event: order_cancel
+ * event: offer_reject
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct OrderCancelData {
-    pub order_id: U64,
+pub struct OfferRejectData {
+    pub offer_id: U64,
+    pub refund: U128,
 }
 
-impl OrderCancelData {
-    pub fn new(order_id: U64) -> Self {
-        Self { order_id: order_id }
+impl OfferRejectData {
+    pub fn new(offer_id: U64, refund: U128) -> Self {
+        Self { offer_id, refund }
     }
 }
 
 /**
- * event: dispute_start
+ * event: offer_withdraw
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct DisputeStartData {
-    pub order_id: U64,
+pub struct OfferWithdrawData {
+    pub offer_id: U64,
+    pub refund: U128,
 }
 
-impl DisputeStartData {
-    pub fn new(order_id: U64) -> Self {
-        Self { order_id: order_id }
+impl OfferWithdrawData {
+    pub fn new(offer_id: U64, refund: U128) -> Self {
+        Self { offer_id, refund }
     }
 }
 
 /**
- * event: dispute_resolve
+ * event: offer_expire
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct DisputeResolveData {
+pub struct OfferExpireData {
+    pub offer_id: U64,
+    pub refund: U128,
+}
+
+impl OfferExpireData {
+    pub fn new(offer_id: U64, refund: U128) -> Self {
+        Self { offer_id, refund }
+    }
+}
+
+/**
+ * event: return_request
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReturnRequestData {
     pub order_id: U64,
-    pub resolution: DisputeResolution,
+    pub buyer_id: AccountId,
+    pub reason: String,
 }
 
-impl DisputeResolveData {
-    pub fn new(order_id: U64, resolution: DisputeResolution) -> Self {
+impl ReturnRequestData {
+    pub fn new(order_id: U64, buyer_id: AccountId, reason: String) -> Self {
         Self {
-            order_id: order_id,
-            resolution: resolution,
+            order_id,
+            buyer_id,
+            reason,
         }
     }
 }
 
 /**
- * event: review_create
+ * event: return_accept
  */
 
 #[skip_serializing_none]
 #[derive(Serialize, Deserialize, Debug)]
-pub struct ReviewCreateData {
-    pub item_id: U64,
-    pub review_id: U64,
-    pub reviewer_id: AccountId,
-    pub rating: u8,
-    pub comment: String,
+pub struct ReturnAcceptData {
+    pub order_id: U64,
+    pub refund: U128,
+    pub carrier: String,
+    pub tracking_number: String,
 }
 
-impl ReviewCreateData {
-    pub fn new(
-        item_id: U64,
-        review_id: U64,
-        reviewer_id: AccountId,
-        rating: u8,
-        comment: String,
-    ) -> Self {
+impl ReturnAcceptData {
+    pub fn new(order_id: U64, refund: U128, shipment: ShippingDetails) -> Self {
         Self {
-            item_id: item_id,
-            review_id: review_id,
-            reviewer_id: reviewer_id,
-            rating: rating,
-            comment,
+            order_id,
+            refund,
+            carrier: shipment.carrier,
+            tracking_number: shipment.tracking_number,
         }
     }
 }
 
+/**
+ * event: return_decline
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReturnDeclineData {
+    pub order_id: U64,
+}
+
+impl ReturnDeclineData {
+    pub fn new(order_id: U64) -> Self {
+        Self { order_id }
+    }
+}
+
+/**
+ * event: return_reserve_release
+ */
+
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReturnReserveReleaseData {
+    pub order_id: U64,
+}
+
+impl ReturnReserveReleaseData {
+    pub fn new(order_id: U64) -> Self {
+        Self { order_id }
+    }
+}
+
 impl NearEvent {
     pub fn new_event(event_kind: Nep297EventKind) -> Self {
         NearEvent::Dms297(Nep297Event {
             version: "0.0.1",
+            actor_id: env::predecessor_account_id(),
             event_kind,
         })
     }
 
+    /// Sets the account that performed the action, for events emitted in callbacks
+    /// or on behalf of the sender of a token transfer.
+    pub fn actor(self, actor_id: AccountId) -> Self {
+        let NearEvent::Dms297(event) = self;
+        NearEvent::Dms297(Nep297Event { actor_id, ..event })
+    }
+
     /**
      * Store events
      */
@@ -315,6 +1261,60 @@ impl NearEvent {
         NearEvent::new_event(Nep297EventKind::StoreDelete())
     }
 
+    pub fn store_confirmation_window_update(data: ConfirmationWindowUpdateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StoreConfirmationWindowUpdate(data))
+    }
+
+    pub fn store_handling_time_update(data: HandlingTimeUpdateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StoreHandlingTimeUpdate(data))
+    }
+
+    pub fn store_return_window_update(data: ReturnWindowUpdateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StoreReturnWindowUpdate(data))
+    }
+
+    pub fn store_pause(data: StorePauseData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StorePause(data))
+    }
+
+    pub fn store_resume() -> Self {
+        NearEvent::new_event(Nep297EventKind::StoreResume())
+    }
+
+    pub fn store_arbiter_propose(data: ArbiterProposeData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StoreArbiterPropose(data))
+    }
+
+    pub fn store_arbiter_cancel() -> Self {
+        NearEvent::new_event(Nep297EventKind::StoreArbiterCancel())
+    }
+
+    /**
+     * Staff events
+     */
+    pub fn staff_grant(data: StaffGrantData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StaffGrant(data))
+    }
+
+    pub fn staff_revoke(data: StaffRevokeData) -> Self {
+        NearEvent::new_event(Nep297EventKind::StaffRevoke(data))
+    }
+
+    /**
+     * Ownership events
+     */
+    pub fn ownership_propose(data: OwnershipProposeData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OwnershipPropose(data))
+    }
+
+    pub fn ownership_cancel() -> Self {
+        NearEvent::new_event(Nep297EventKind::OwnershipCancel())
+    }
+
+    pub fn ownership_transfer(data: OwnershipTransferData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OwnershipTransfer(data))
+    }
+
     /**
      * Item events
      */
@@ -330,6 +1330,41 @@ impl NearEvent {
         NearEvent::new_event(Nep297EventKind::ItemDelete(data))
     }
 
+    pub fn item_stock_update(data: ItemStockUpdateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ItemStockUpdate(data))
+    }
+
+    pub fn item_milestones_update(data: ItemMilestonesUpdateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ItemMilestonesUpdate(data))
+    }
+
+    pub fn item_status_update(data: ItemStatusUpdateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ItemStatusUpdate(data))
+    }
+
+    /**
+     * Collection events
+     */
+    pub fn collection_create(data: CollectionData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CollectionCreate(data))
+    }
+
+    pub fn collection_update(data: CollectionData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CollectionUpdate(data))
+    }
+
+    pub fn collection_delete(data: CollectionDeleteData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CollectionDelete(data))
+    }
+
+    pub fn collection_add_item(data: CollectionItemData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CollectionAddItem(data))
+    }
+
+    pub fn collection_remove_item(data: CollectionItemData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CollectionRemoveItem(data))
+    }
+
     /**
      * Order events
      */
@@ -349,6 +1384,37 @@ impl NearEvent {
         NearEvent::new_event(Nep297EventKind::OrderCancel(data))
     }
 
+    pub fn order_late_refund(data: OrderLateRefundData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OrderLateRefund(data))
+    }
+
+    pub fn order_refund(data: OrderRefundData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OrderRefund(data))
+    }
+
+    pub fn order_commission(data: OrderCommissionData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OrderCommission(data))
+    }
+
+    pub fn milestone_approve(data: MilestoneApproveData) -> Self {
+        NearEvent::new_event(Nep297EventKind::MilestoneApprove(data))
+    }
+
+    pub fn cart_checkout(data: CartCheckoutData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CartCheckout(data))
+    }
+
+    /**
+     * Balance events
+     */
+    pub fn balance_withdraw(data: BalanceWithdrawData) -> Self {
+        NearEvent::new_event(Nep297EventKind::BalanceWithdraw(data))
+    }
+
+    pub fn balance_restore(data: BalanceRestoreData) -> Self {
+        NearEvent::new_event(Nep297EventKind::BalanceRestore(data))
+    }
+
     /**
      * Dispute events
      */
@@ -356,6 +1422,18 @@ impl NearEvent {
         NearEvent::new_event(Nep297EventKind::DisputeStart(data))
     }
 
+    pub fn settlement_attempt(data: SettlementAttemptData) -> Self {
+        NearEvent::new_event(Nep297EventKind::SettlementAttempt(data))
+    }
+
+    pub fn settlement_outcome(data: SettlementOutcomeData) -> Self {
+        NearEvent::new_event(Nep297EventKind::SettlementOutcome(data))
+    }
+
+    pub fn settlement_cancel(data: SettlementCancelData) -> Self {
+        NearEvent::new_event(Nep297EventKind::SettlementCancel(data))
+    }
+
     pub fn dispute_resolve(data: DisputeResolveData) -> Self {
         NearEvent::new_event(Nep297EventKind::DisputeResolve(data))
     }
@@ -367,6 +1445,78 @@ impl NearEvent {
         NearEvent::new_event(Nep297EventKind::ReviewCreate(data))
     }
 
+    /**
+     * Coupon events
+     */
+    pub fn coupon_create(data: CouponCreateData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CouponCreate(data))
+    }
+
+    pub fn coupon_delete(data: CouponDeleteData) -> Self {
+        NearEvent::new_event(Nep297EventKind::CouponDelete(data))
+    }
+
+    /**
+     * Auction events
+     */
+    pub fn auction_start(data: AuctionStartData) -> Self {
+        NearEvent::new_event(Nep297EventKind::AuctionStart(data))
+    }
+
+    pub fn auction_bid(data: AuctionBidData) -> Self {
+        NearEvent::new_event(Nep297EventKind::AuctionBid(data))
+    }
+
+    pub fn auction_settle(data: AuctionSettleData) -> Self {
+        NearEvent::new_event(Nep297EventKind::AuctionSettle(data))
+    }
+
+    /**
+     * Offer events
+     */
+    pub fn offer_make(data: OfferMakeData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OfferMake(data))
+    }
+
+    pub fn offer_counter(data: OfferCounterData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OfferCounter(data))
+    }
+
+    pub fn offer_accept(data: OfferAcceptData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OfferAccept(data))
+    }
+
+    pub fn offer_reject(data: OfferRejectData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OfferReject(data))
+    }
+
+    pub fn offer_withdraw(data: OfferWithdrawData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OfferWithdraw(data))
+    }
+
+    pub fn offer_expire(data: OfferExpireData) -> Self {
+        NearEvent::new_event(Nep297EventKind::OfferExpire(data))
+    }
+
+    /**
+     * Return events
+     */
+    pub fn return_request(data: ReturnRequestData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ReturnRequest(data))
+    }
+
+    pub fn return_accept(data: ReturnAcceptData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ReturnAccept(data))
+    }
+
+    pub fn return_decline(data: ReturnDeclineData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ReturnDecline(data))
+    }
+
+    pub fn return_reserve_release(data: ReturnReserveReleaseData) -> Self {
+        NearEvent::new_event(Nep297EventKind::ReturnReserveRelease(data))
+    }
+
     /**
      * Helper functions
      */
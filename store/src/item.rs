@@ -8,6 +8,9 @@
  * - item_create
  * - item_update
  * - item_delete
+ * - item_restock
+ * - item_set_stock
+ * - item_set_status
  *
  *
  */
@@ -15,15 +18,150 @@ use crate::*;
 
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Item {
-    pub price: Balance,
+    pub price: Balance,                         // price in NEAR
+    pub ft_prices: HashMap<AccountId, Balance>, // price in each accepted fungible token
+    pub stock: u64,                             // units left, the sum of the variants if any
     pub status: ItemStatus,
+    pub variants: HashMap<String, ItemVariant>, // ex. "xl-red", bought by key
+    pub auction: Option<Auction>,               // sold by auction instead of at `price`
+    pub milestones: Vec<ItemMilestone>,         // paid in stages, empty for items paid at once
+    pub collections: Vec<u64>,                  // ids of the collections the item is in
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq)]
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ItemVariant {
+    pub title: String,
+    pub price: Balance,
+    pub ft_prices: HashMap<AccountId, Balance>,
+    pub stock: u64,
+    pub images: Option<Vec<String>>,
+}
+
+impl Item {
+    pub fn json_ft_prices(&self) -> HashMap<AccountId, U128> {
+        json_ft_prices(&self.ft_prices)
+    }
+
+    pub fn json_variants(&self) -> HashMap<String, JsonItemVariant> {
+        self.variants
+            .iter()
+            .map(|(key, variant)| {
+                (
+                    key.clone(),
+                    JsonItemVariant {
+                        title: variant.title.clone(),
+                        price: U128(variant.price),
+                        ft_prices: json_ft_prices(&variant.ft_prices),
+                        stock: variant.stock,
+                        images: variant.images.clone(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Unit price in NEAR or in the given token, of the chosen variant if the item has any.
+    pub fn unit_price(&self, variant: &Option<String>, ft_token_id: &Option<AccountId>) -> Balance {
+        require!(self.auction.is_none(), "Item is sold by auction");
+        let (price, ft_prices) = match variant {
+            Some(key) => {
+                let variant = self.variants.get(key).expect("Variant does not exist");
+                (variant.price, &variant.ft_prices)
+            }
+            None => {
+                require!(
+                    self.variants.is_empty(),
+                    "A variant must be chosen for this item"
+                );
+                (self.price, &self.ft_prices)
+            }
+        };
+        match ft_token_id {
+            None => price,
+            Some(ft_token_id) => *ft_prices
+                .get(ft_token_id)
+                .expect("Item is not priced in this token"),
+        }
+    }
+
+    /// Units left of the chosen variant if the item has any.
+    pub fn stock_of(&self, variant: &Option<String>) -> u64 {
+        match variant {
+            Some(key) => {
+                self.variants
+                    .get(key)
+                    .expect("Variant does not exist")
+                    .stock
+            }
+            None => {
+                require!(
+                    self.variants.is_empty(),
+                    "A variant must be chosen for this item"
+                );
+                self.stock
+            }
+        }
+    }
+
+    /// Status as of now, a scheduled item is active from its publishing date.
+    pub fn current_status(&self) -> ItemStatus {
+        match self.status {
+            ItemStatus::Scheduled(publish_at) if env::block_timestamp_ms() >= publish_at => {
+                if self.stock > 0 {
+                    ItemStatus::Active
+                } else {
+                    ItemStatus::Inactive
+                }
+            }
+            _ => self.status.clone(),
+        }
+    }
+
+    /// Deactivates the item when it sells out and reactivates it on restock,
+    /// a scheduled item that is being sold was published, only items on sale sell out.
+    pub fn sync_status(&mut self) {
+        self.status = self.current_status();
+        if self.stock == 0 && self.status == ItemStatus::Active {
+            self.status = ItemStatus::Inactive;
+        } else if self.stock > 0 && self.status == ItemStatus::Inactive {
+            self.status = ItemStatus::Active;
+        }
+    }
+
+    /// Whether the item can be bought, bid on or offered for.
+    pub fn is_available(&self) -> bool {
+        self.current_status() == ItemStatus::Active
+    }
+}
+
+fn json_ft_prices(ft_prices: &HashMap<AccountId, Balance>) -> HashMap<AccountId, U128> {
+    ft_prices
+        .iter()
+        .map(|(ft_token_id, amount)| (ft_token_id.clone(), U128(*amount)))
+        .collect()
+}
+
+// Variant as sent to item_create/item_update and returned from view calls
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonItemVariant {
+    pub title: String, // required, ex. "XL / Red"
+    pub price: U128,   // required, price in NEAR
+    #[serde(default)]
+    pub ft_prices: HashMap<AccountId, U128>, // optional, price in accepted tokens
+    pub stock: u64,    // required
+    pub images: Option<Vec<String>>, // optional, ex. ["https://example.com/red.png"]
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Clone)]
 #[serde(crate = "near_sdk::serde")]
 pub enum ItemStatus {
     Active,
-    Inactive,
+    Inactive,       // sold out, active again on restock
+    Draft,          // not published yet
+    Scheduled(u64), // published on its own at the timestamp, in ms
+    Paused,         // taken off sale by the owner
+    Archived,       // no longer sold, kept for the history of its orders
 }
 
 // The Json Item is what will be returned from view calls.
@@ -32,10 +170,24 @@ pub enum ItemStatus {
 pub struct JsonItem {
     pub id: U64,
     pub price: U128,
+    pub ft_prices: HashMap<AccountId, U128>,
+    pub stock: u64,
     pub status: ItemStatus,
+    pub variants: HashMap<String, JsonItemVariant>,
+    pub auction: Option<JsonAuction>,
+    pub milestones: Vec<ItemMilestone>,
+    pub collections: Vec<U64>,
     pub metadata: ItemMetadata,
 }
 
+// Tag with the number of items using it, returned from view calls
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonTag {
+    pub tag: String,
+    pub items: u64,
+}
+
 pub trait ItemProvider {
     fn get_item(&self, item_id: U64) -> Option<JsonItem>;
 }
@@ -48,7 +200,13 @@ impl ItemProvider for Contract {
             Some(JsonItem {
                 id: item_id,
                 price: item.price.into(),
-                status: item.status,
+                ft_prices: item.json_ft_prices(),
+                stock: item.stock,
+                variants: item.json_variants(),
+                auction: item.auction.as_ref().map(|auction| auction.to_json()),
+                milestones: item.milestones.clone(),
+                collections: item.collections.iter().map(|id| U64(*id)).collect(),
+                status: item.current_status(),
                 metadata,
             })
         } else {
@@ -58,41 +216,96 @@ impl ItemProvider for Contract {
 }
 
 pub trait ItemManager {
-    fn item_create(&mut self, price: U128, metadata: ItemMetadata) -> U64;
-    fn item_update(&mut self, item_id: U64, price: U128, metadata: ItemMetadata);
+    fn item_create(
+        &mut self,
+        price: U128,
+        metadata: ItemMetadata,
+        ft_prices: Option<HashMap<AccountId, U128>>,
+        stock: u64,
+        variants: Option<HashMap<String, JsonItemVariant>>,
+        status: Option<ItemStatus>,
+    ) -> U64;
+    fn item_update(
+        &mut self,
+        item_id: U64,
+        price: U128,
+        metadata: ItemMetadata,
+        ft_prices: Option<HashMap<AccountId, U128>>,
+        variants: Option<HashMap<String, JsonItemVariant>>,
+    );
     fn item_delete(&mut self, item_id: U64);
+    fn item_restock(&mut self, item_id: U64, quantity: u64, variant: Option<String>);
+    fn item_set_stock(&mut self, item_id: U64, stock: u64, variant: Option<String>);
+    fn item_set_status(&mut self, item_id: U64, status: ItemStatus);
 }
 
 #[near_bindgen]
 impl ItemManager for Contract {
     #[payable]
-    fn item_create(&mut self, price: U128, metadata: ItemMetadata) -> U64 {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner_id,
-            "Only owner can create a item"
-        );
-        assert!(
-            env::attached_deposit() >= 50_000_000_000_000_000_000_000,
-            "Attached deposit must be at least 0.05 NEAR to cover storage costs"
+    fn item_create(
+        &mut self,
+        price: U128,
+        metadata: ItemMetadata,
+        ft_prices: Option<HashMap<AccountId, U128>>,
+        stock: u64,
+        variants: Option<HashMap<String, JsonItemVariant>>,
+        status: Option<ItemStatus>,
+    ) -> U64 {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can create a item",
         );
         assert!(
             price >= U128(1_000_000_000_000_000_000_000_000),
             "Minimum price is 1 NEAR"
         );
-        let item_id = self.items_metadata_by_id.len();
+
+        // items with variants hold the sum of their variants' stock
+        let variants = self.internal_variants(variants);
+        let stock = if variants.is_empty() {
+            stock
+        } else {
+            variants.values().map(|variant| variant.stock).sum()
+        };
+        assert!(stock > 0, "Stock must be greater than 0");
+
+        //items can be created as drafts or scheduled, they are active otherwise
+        let status = status.unwrap_or(ItemStatus::Active);
+        self.internal_assert_status(&status);
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        //ids are never reused, deleted items leave a gap
+        let item_id = self.next_item_id;
+        self.next_item_id += 1;
         let item = Item {
             price: price.into(),
-            status: ItemStatus::Active,
+            ft_prices: self.internal_ft_prices(ft_prices),
+            stock,
+            status,
+            variants,
+            auction: None,
+            milestones: Vec::new(),
+            collections: Vec::new(),
         };
         self.items_by_id.insert(&item_id, &item);
         self.items_metadata_by_id.insert(&item_id, &metadata);
+        self.internal_index_tags(item_id, &None, &metadata.tags);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
 
         // Emit a NearEvent
         NearEvent::item_create(ItemCreateData::new(
             U64(item_id),
             price,
-            item.status,
+            item.json_ft_prices(),
+            item.stock,
+            item.json_variants(),
+            item.current_status(),
             metadata,
         ))
         .emit();
@@ -100,11 +313,17 @@ impl ItemManager for Contract {
         U64(item_id)
     }
 
-    fn item_update(&mut self, item_id: U64, price: U128, metadata: ItemMetadata) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner_id,
-            "Only owner can update a item"
+    fn item_update(
+        &mut self,
+        item_id: U64,
+        price: U128,
+        metadata: ItemMetadata,
+        ft_prices: Option<HashMap<AccountId, U128>>,
+        variants: Option<HashMap<String, JsonItemVariant>>,
+    ) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can update a item",
         );
 
         let orders = &self.orders_by_item_id.get(&item_id.into());
@@ -120,39 +339,372 @@ impl ItemManager for Contract {
             });
         }
 
+        let initial_storage_usage = env::storage_usage();
+
         let mut item = self.items_by_id.get(&item_id.into()).unwrap();
         item.price = price.into();
+        item.ft_prices = self.internal_ft_prices(ft_prices);
+        let had_variants = !item.variants.is_empty();
+        item.variants = self.internal_variants(variants);
+        if !item.variants.is_empty() {
+            item.stock = item.variants.values().map(|variant| variant.stock).sum();
+        } else if had_variants {
+            //the units were the variants', the item's own stock is set again with item_set_stock
+            item.stock = 0;
+        }
+        item.sync_status();
         self.items_by_id.insert(&item_id.into(), &item);
-        self.items_metadata_by_id.insert(&item_id.into(), &metadata);
+        let previous_metadata = self
+            .items_metadata_by_id
+            .insert(&item_id.into(), &metadata)
+            .unwrap();
+        self.internal_index_tags(item_id.into(), &previous_metadata.tags, &metadata.tags);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
 
         // Emit a NearEvent
-        NearEvent::item_update(ItemUpdateData::new(item_id, price, item.status, metadata)).emit();
+        NearEvent::item_update(ItemUpdateData::new(
+            item_id,
+            price,
+            item.json_ft_prices(),
+            item.stock,
+            item.json_variants(),
+            item.current_status(),
+            metadata,
+        ))
+        .emit();
     }
 
     fn item_delete(&mut self, item_id: U64) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner_id,
-            "Only owner can delete a item"
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can delete a item",
         );
 
-        let orders = &self.orders_by_item_id.get(&item_id.into());
-        if orders.is_some() {
-            orders.as_ref().unwrap().iter().for_each(|order_id| {
-                let order = self.orders_by_id.get(&order_id).unwrap();
-                assert!(
-                    order.status == OrderStatus::Pending
-                        || order.status == OrderStatus::Disputed
-                        || order.status == OrderStatus::Shipped,
-                    "Can't update item while there are active orders or disputes for this item"
-                );
-            });
+        //bids are held in escrow until the auction is settled
+        if let Some(item) = self.items_by_id.get(&item_id.into()) {
+            require!(
+                !matches!(
+                    item.auction,
+                    Some(Auction {
+                        bidder_id: Some(_),
+                        ..
+                    })
+                ),
+                "Can't delete an item with bids"
+            );
+        }
+
+        //open offers can't be accepted anymore, their escrow goes back to the buyers
+        self.internal_reject_offers(item_id.into());
+
+        //items with orders are archived instead, their orders still point to them,
+        //orders in progress keep going and return their units to the archived item
+        if self
+            .orders_by_item_id
+            .get(&item_id.into())
+            .is_some_and(|orders| !orders.is_empty())
+        {
+            let mut item = self
+                .items_by_id
+                .get(&item_id.into())
+                .expect("Item does not exist");
+            //archived items keep their collections, their orders still link to them and
+            //the item page the buyers come back to should show where it was listed
+            item.status = ItemStatus::Archived;
+            self.items_by_id.insert(&item_id.into(), &item);
+
+            // Emit a NearEvent
+            NearEvent::item_status_update(ItemStatusUpdateData::new(item_id, item.status)).emit();
+            return;
+        }
+
+        let initial_storage_usage = env::storage_usage();
+
+        //the item leaves its tags and its collections
+        let tags = self
+            .items_metadata_by_id
+            .get(&item_id.into())
+            .and_then(|metadata| metadata.tags);
+        self.internal_index_tags(item_id.into(), &tags, &None);
+        if let Some(item) = self.items_by_id.get(&item_id.into()) {
+            for collection_id in item.collections {
+                self.internal_collection_unindex(collection_id, item_id.into());
+            }
         }
 
         self.items_by_id.remove(&item_id.into());
         self.items_metadata_by_id.remove(&item_id.into());
 
+        //the freed bytes are released from the owner's storage balance
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
         // Emit a NearEvent
         NearEvent::item_delete(ItemDeleteData::new(item_id)).emit();
     }
+
+    fn item_restock(&mut self, item_id: U64, quantity: u64, variant: Option<String>) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can restock a item",
+        );
+        assert!(quantity > 0, "Quantity must be greater than 0");
+
+        let item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        let stock = item.stock_of(&variant) + quantity;
+        self.internal_set_stock(
+            item_id.into(),
+            item,
+            variant,
+            stock,
+            env::predecessor_account_id(),
+        );
+    }
+
+    fn item_set_stock(&mut self, item_id: U64, stock: u64, variant: Option<String>) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can set the stock of a item",
+        );
+
+        let item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        match &variant {
+            Some(key) => require!(item.variants.contains_key(key), "Variant does not exist"),
+            None => require!(
+                item.variants.is_empty(),
+                "A variant must be chosen for this item"
+            ),
+        }
+        self.internal_set_stock(
+            item_id.into(),
+            item,
+            variant,
+            stock,
+            env::predecessor_account_id(),
+        );
+    }
+
+    fn item_set_status(&mut self, item_id: U64, status: ItemStatus) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can set the status of a item",
+        );
+        self.internal_assert_status(&status);
+
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+
+        //bids are held in escrow until the auction is settled
+        require!(
+            status == ItemStatus::Active
+                || !matches!(
+                    item.auction,
+                    Some(Auction {
+                        bidder_id: Some(_),
+                        ..
+                    })
+                ),
+            "Can't take an item with bids off sale"
+        );
+
+        //an item without stock is published sold out
+        item.status = if status == ItemStatus::Active && item.stock == 0 {
+            ItemStatus::Inactive
+        } else {
+            status
+        };
+        self.items_by_id.insert(&item_id.into(), &item);
+
+        // Emit a NearEvent
+        NearEvent::item_status_update(ItemStatusUpdateData::new(item_id, item.status)).emit();
+    }
+}
+
+impl Contract {
+    /// Checks a status the owner chose, sold out is only set by the stock.
+    fn internal_assert_status(&self, status: &ItemStatus) {
+        require!(
+            *status != ItemStatus::Inactive,
+            "Items become inactive when they sell out"
+        );
+        if let ItemStatus::Scheduled(publish_at) = status {
+            require!(
+                *publish_at > env::block_timestamp_ms(),
+                "Publishing date must be in the future"
+            );
+        }
+    }
+
+    /// Moves the item from the index of its previous tags to the index of the new ones.
+    pub(crate) fn internal_index_tags(
+        &mut self,
+        item_id: u64,
+        previous_tags: &Option<Vec<String>>,
+        tags: &Option<Vec<String>>,
+    ) {
+        let previous_tags = previous_tags.clone().unwrap_or_default();
+        let tags = tags.clone().unwrap_or_default();
+
+        for tag in previous_tags.iter().filter(|tag| !tags.contains(tag)) {
+            if let Some(mut item_ids) = self.items_by_tag.get(tag) {
+                item_ids.remove(&item_id);
+                //tags without items are no longer listed
+                if item_ids.is_empty() {
+                    self.items_by_tag.remove(tag);
+                } else {
+                    self.items_by_tag.insert(tag, &item_ids);
+                }
+            }
+        }
+
+        for tag in tags.iter().filter(|tag| !previous_tags.contains(tag)) {
+            let mut item_ids = self.items_by_tag.get(tag).unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::ItemsByTagInner {
+                        tag_hash: env::sha256(tag.as_bytes()),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+            item_ids.insert(&item_id);
+            self.items_by_tag.insert(tag, &item_ids);
+        }
+    }
+
+    /// Validates the token prices of an item against the store's accepted tokens.
+    pub(crate) fn internal_ft_prices(
+        &self,
+        ft_prices: Option<HashMap<AccountId, U128>>,
+    ) -> HashMap<AccountId, Balance> {
+        ft_prices
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(ft_token_id, amount)| {
+                require!(
+                    self.ft_token_ids.contains(&ft_token_id),
+                    format!("Token {} is not accepted by this store", ft_token_id)
+                );
+                require!(amount.0 > 0, "Token price must be greater than 0");
+                (ft_token_id, amount.0)
+            })
+            .collect()
+    }
+
+    /// Validates the variants of an item, each priced like the item itself.
+    pub(crate) fn internal_variants(
+        &self,
+        variants: Option<HashMap<String, JsonItemVariant>>,
+    ) -> HashMap<String, ItemVariant> {
+        variants
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, variant)| {
+                require!(!key.is_empty(), "Variant key can't be empty");
+                require!(
+                    variant.price >= U128(1_000_000_000_000_000_000_000_000),
+                    "Minimum price is 1 NEAR"
+                );
+                (
+                    key,
+                    ItemVariant {
+                        title: variant.title,
+                        price: variant.price.into(),
+                        ft_prices: self.internal_ft_prices(Some(variant.ft_prices)),
+                        stock: variant.stock,
+                        images: variant.images,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Takes `quantity` units out of the item's stock, or its variant's, for a new order.
+    pub(crate) fn internal_reserve_stock(
+        &mut self,
+        item_id: u64,
+        variant: &Option<String>,
+        quantity: u64,
+        buyer_id: &AccountId,
+    ) {
+        require!(quantity > 0, "Quantity must be greater than 0");
+        let item = self.items_by_id.get(&item_id).expect("Item does not exist");
+        let stock = item.stock_of(variant);
+        require!(stock >= quantity, "Not enough stock for this order");
+        self.internal_set_stock(
+            item_id,
+            item,
+            variant.clone(),
+            stock - quantity,
+            buyer_id.clone(),
+        );
+    }
+
+    /// Puts the units of a cancelled or refunded order back into the item's stock.
+    pub(crate) fn internal_return_stock(
+        &mut self,
+        item_id: u64,
+        variant: &Option<String>,
+        quantity: u64,
+    ) {
+        if let Some(item) = self.items_by_id.get(&item_id) {
+            // the variant may have been removed since the order was made
+            if let Some(key) = variant {
+                if !item.variants.contains_key(key) {
+                    return;
+                }
+            }
+            let stock = item.stock_of(variant) + quantity;
+            self.internal_set_stock(
+                item_id,
+                item,
+                variant.clone(),
+                stock,
+                env::predecessor_account_id(),
+            );
+        }
+    }
+
+    /// Saves the new stock, deactivating the item when it sells out and reactivating it on restock.
+    /// `actor_id` is who changed it, the buyer for a purchase paid with a token transfer.
+    pub(crate) fn internal_set_stock(
+        &mut self,
+        item_id: u64,
+        mut item: Item,
+        variant: Option<String>,
+        stock: u64,
+        actor_id: AccountId,
+    ) {
+        match &variant {
+            Some(key) => {
+                item.variants
+                    .get_mut(key)
+                    .expect("Variant does not exist")
+                    .stock = stock;
+                item.stock = item.variants.values().map(|variant| variant.stock).sum();
+            }
+            None => item.stock = stock,
+        }
+        item.sync_status();
+        self.items_by_id.insert(&item_id, &item);
+
+        // Emit a NearEvent
+        NearEvent::item_stock_update(ItemStockUpdateData::new(
+            U64(item_id),
+            variant.clone(),
+            variant.map(|_| stock),
+            item.stock,
+            item.status,
+        ))
+        .actor(actor_id)
+        .emit();
+    }
 }
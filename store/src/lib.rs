@@ -1,17 +1,40 @@
+use std::collections::HashMap;
+
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedMap, UnorderedSet};
+use near_sdk::collections::{LazyOption, LookupMap, TreeMap, UnorderedMap, UnorderedSet};
 use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::{env, near_bindgen, require, AccountId, Balance, Gas, PanicOnDefault, Promise, PromiseError};
+use near_sdk::{
+    env, near_bindgen, require, AccountId, Balance, Gas, PanicOnDefault, Promise, PromiseError,
+    PromiseOrValue, StorageUsage, ONE_YOCTO,
+};
 
+mod arbiter;
+mod auction;
+mod balance;
+mod collection;
+mod coupon;
 mod dispute;
 mod enumeration;
 mod event;
 mod item;
 mod metadata;
+mod milestone;
+mod offer;
 mod order;
+mod ownership;
+mod payment;
+mod returns;
 mod review;
+mod staff;
+mod storage;
 
+#[allow(unused_imports)]
+use crate::arbiter::*;
+use crate::auction::*;
+use crate::balance::*;
+use crate::collection::*;
+use crate::coupon::*;
 #[allow(unused_imports)]
 use crate::dispute::*;
 #[allow(unused_imports)]
@@ -19,8 +42,18 @@ use crate::enumeration::*;
 use crate::event::*;
 use crate::item::*;
 use crate::metadata::*;
+use crate::milestone::*;
+use crate::offer::*;
 use crate::order::*;
+#[allow(unused_imports)]
+use crate::ownership::*;
+#[allow(unused_imports)]
+use crate::payment::*;
+use crate::returns::*;
 use crate::review::*;
+use crate::staff::*;
+#[allow(unused_imports)]
+use crate::storage::*;
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
@@ -28,15 +61,59 @@ pub struct Contract {
     /// Store owner
     pub owner_id: AccountId,
 
+    /// Account the owner proposed to transfer the store to
+    pub pending_owner_id: Option<AccountId>,
+
+    /// Owner the factory has on record, behind `owner_id` until the factory confirms a transfer
+    pub factory_owner_id: AccountId,
+
+    /// Store staff, the owner grants them roles
+    pub staff: UnorderedMap<AccountId, StaffRole>,
+
     /// Store middleman
     pub arbiter_id: AccountId,
 
+    /// Arbiter the owner proposed, it takes over once no order is disputed
+    pub pending_arbiter_id: Option<AccountId>,
+
+    /// Orders disputed, settling or whose settlement failed, they keep the current arbiter
+    pub open_disputes: u64,
+
+    /// Marketplace commission, taken from the seller's payouts
+    pub treasury_id: AccountId,
+    pub commission_bps: u16,
+
+    /// Time the buyer has to confirm a shipped order before anyone can finalize it, in ms
+    pub confirmation_window: u64,
+
+    /// Time the seller has to ship a pending order before the buyer can get a refund, in ms
+    pub handling_time: u64,
+
+    /// Time the buyer has to return a completed order, the seller's payout is held meanwhile, in ms
+    pub return_window: u64,
+
+    /// Set while the store doesn't accept new sales
+    pub pause: Option<StorePause>,
+
+    /// Store statistics
+    pub stats: StoreStats,
+
     /// Store metadata
     pub metadata: LazyOption<StoreMetadata>,
 
     /// Items
     pub items_by_id: LookupMap<u64, Item>,
     pub items_metadata_by_id: UnorderedMap<u64, ItemMetadata>,
+    pub next_item_id: u64,
+
+    /// Items indexed by the tags of their metadata
+    pub items_by_tag: UnorderedMap<String, UnorderedSet<u64>>,
+
+    /// Collections, items are indexed by collection
+    pub collections_by_id: UnorderedMap<u64, CollectionMetadata>,
+    pub collections_by_position: TreeMap<(u32, u64), ()>,
+    pub items_by_collection_id: LookupMap<u64, UnorderedSet<u64>>,
+    pub next_collection_id: u64,
 
     /// Orders
     pub orders_by_id: UnorderedMap<u64, Order>,
@@ -47,8 +124,39 @@ pub struct Contract {
     pub reviews_by_id: UnorderedMap<u64, Review>,
     pub reviews_by_account_id: LookupMap<AccountId, UnorderedSet<u64>>,
     pub reviews_by_item_id: LookupMap<u64, UnorderedSet<u64>>,
+
+    /// Coupons, by code
+    pub coupons: UnorderedMap<String, Coupon>,
+    pub coupon_uses_by_account: LookupMap<(String, AccountId), u64>,
+
+    /// Offers, open ones are indexed by item
+    pub offers_by_id: UnorderedMap<u64, Offer>,
+    pub offers_by_item_id: LookupMap<u64, UnorderedSet<u64>>,
+
+    /// Settled funds waiting to be withdrawn, by account
+    pub balances: LookupMap<AccountId, ClaimableBalance>,
+
+    /// Totals of the funds held in escrow and of the claimable balances, by token
+    pub escrow: ClaimableBalance,
+    pub claimable: ClaimableBalance,
+
+    /// Fungible tokens accepted as payment
+    pub ft_token_ids: UnorderedSet<AccountId>,
+
+    /// Storage deposits (NEP-145)
+    pub storage_accounts: LookupMap<AccountId, StorageAccount>,
+    pub account_storage_usage: StorageUsage,
 }
 
+/// Commissions are expressed in basis points, 10000 being the whole amount.
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+/// Buyers have 14 days to confirm a shipped order unless the store sets another window.
+pub const DEFAULT_CONFIRMATION_WINDOW: u64 = 14 * 24 * 60 * 60 * 1000;
+
+/// Sellers have 7 days to ship an order unless the store sets another handling time.
+pub const DEFAULT_HANDLING_TIME: u64 = 7 * 24 * 60 * 60 * 1000;
+
 /// Helper structure for keys of the persistent collections.
 #[derive(BorshSerialize)]
 pub enum StorageKey {
@@ -65,19 +173,45 @@ pub enum StorageKey {
     ReviewsByAccountIdInner { account_id_hash: Vec<u8> },
     ReviewsByItemId,
     ReviewsByItemIdInner { item_id_hash: Vec<u8> },
+    FtTokenIds,
+    StorageAccounts,
+    Coupons,
+    CouponUsesByAccount,
+    OffersById,
+    OffersByItemId,
+    OffersByItemIdInner { item_id_hash: Vec<u8> },
+    Balances,
+    Staff,
+    Collections,
+    ItemsByCollectionId,
+    ItemsByCollectionIdInner { collection_id_hash: Vec<u8> },
+    ItemsByTag,
+    ItemsByTagInner { tag_hash: Vec<u8> },
+    CollectionsByPosition,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
-    pub fn new(owner_id: AccountId, metadata: StoreMetadata) -> Self {
+    pub fn new(
+        owner_id: AccountId,
+        metadata: StoreMetadata,
+        arbiter_id: AccountId,
+        treasury_id: AccountId,
+        commission_bps: u16,
+    ) -> Self {
         require!(
             env::is_valid_account_id(owner_id.as_bytes()),
             "Owner's account ID is invalid"
         );
-
-        // overriding arbiter_id currently
-        let arbiter_id: AccountId = "ddd7.testnet".parse().unwrap();
+        require!(
+            env::is_valid_account_id(arbiter_id.as_bytes()),
+            "Arbiter's account ID is invalid"
+        );
+        require!(
+            commission_bps <= MAX_BASIS_POINTS,
+            "Commission can't be more than 10000 basis points"
+        );
 
         let metadata = StoreMetadata {
             created_at: Some(env::block_timestamp_ms().to_string()),
@@ -85,9 +219,20 @@ impl Contract {
             ..metadata.clone()
         };
 
-        let this = Self {
+        let mut this = Self {
             owner_id: owner_id.clone(),
+            pending_owner_id: None,
+            factory_owner_id: owner_id.clone(),
             arbiter_id: arbiter_id.clone(),
+            pending_arbiter_id: None,
+            open_disputes: 0,
+            treasury_id: treasury_id.clone(),
+            commission_bps,
+            confirmation_window: DEFAULT_CONFIRMATION_WINDOW,
+            handling_time: DEFAULT_HANDLING_TIME,
+            return_window: 0,
+            pause: None,
+            stats: StoreStats::default(),
             metadata: LazyOption::new(
                 StorageKey::StoreMetadata.try_to_vec().unwrap(),
                 Some(&metadata),
@@ -96,6 +241,7 @@ impl Contract {
             items_metadata_by_id: UnorderedMap::new(
                 StorageKey::ItemsMetadataById.try_to_vec().unwrap(),
             ),
+            next_item_id: 0,
             orders_by_id: UnorderedMap::new(StorageKey::OrdersById.try_to_vec().unwrap()),
             orders_by_account_id: LookupMap::new(
                 StorageKey::OrdersByAccountId.try_to_vec().unwrap(),
@@ -106,10 +252,41 @@ impl Contract {
                 StorageKey::ReviewsByAccountId.try_to_vec().unwrap(),
             ),
             reviews_by_item_id: LookupMap::new(StorageKey::ReviewsByItemId.try_to_vec().unwrap()),
+            ft_token_ids: UnorderedSet::new(StorageKey::FtTokenIds.try_to_vec().unwrap()),
+            storage_accounts: LookupMap::new(StorageKey::StorageAccounts.try_to_vec().unwrap()),
+            account_storage_usage: 0,
+            coupons: UnorderedMap::new(StorageKey::Coupons.try_to_vec().unwrap()),
+            coupon_uses_by_account: LookupMap::new(
+                StorageKey::CouponUsesByAccount.try_to_vec().unwrap(),
+            ),
+            offers_by_id: UnorderedMap::new(StorageKey::OffersById.try_to_vec().unwrap()),
+            offers_by_item_id: LookupMap::new(StorageKey::OffersByItemId.try_to_vec().unwrap()),
+            balances: LookupMap::new(StorageKey::Balances.try_to_vec().unwrap()),
+            escrow: ClaimableBalance::default(),
+            claimable: ClaimableBalance::default(),
+            staff: UnorderedMap::new(StorageKey::Staff.try_to_vec().unwrap()),
+            collections_by_id: UnorderedMap::new(StorageKey::Collections.try_to_vec().unwrap()),
+            collections_by_position: TreeMap::new(
+                StorageKey::CollectionsByPosition.try_to_vec().unwrap(),
+            ),
+            items_by_collection_id: LookupMap::new(
+                StorageKey::ItemsByCollectionId.try_to_vec().unwrap(),
+            ),
+            next_collection_id: 0,
+            items_by_tag: UnorderedMap::new(StorageKey::ItemsByTag.try_to_vec().unwrap()),
         };
 
+        this.internal_measure_account_storage_usage();
+
         // Emit a NearEvent
-        NearEvent::store_create(StoreCreateData::new(owner_id, arbiter_id, metadata)).emit();
+        NearEvent::store_create(StoreCreateData::new(
+            owner_id,
+            arbiter_id,
+            treasury_id,
+            commission_bps,
+            metadata,
+        ))
+        .emit();
 
         this
     }
@@ -120,17 +297,35 @@ impl Contract {
             env::predecessor_account_id() == self.owner_id,
             "Can only be called by the owner"
         );
+        //the factory removes the store from its owner's list, it must list it under this one
+        require!(
+            self.factory_owner_id == self.owner_id,
+            "Factory still lists the store under its previous owner, call ownership_sync first"
+        );
 
         let orders = &self.orders_by_id;
         for order in orders.values() {
             require!(
-                order.status == OrderStatus::Pending
-                    || order.status == OrderStatus::Shipped
-                    || order.status == OrderStatus::Disputed,
+                order.status != OrderStatus::Pending
+                    && order.status != OrderStatus::Shipped
+                    && order.status != OrderStatus::Disputed
+                    && order.status != OrderStatus::Settling
+                    && order.status != OrderStatus::SettlementFailed
+                    && order.reserve_until.is_none(),
                 "Can't delete store with orders in progress"
             );
         }
 
+        //open offers and bids are still in escrow, settled funds wait to be withdrawn
+        require!(
+            self.escrow.is_empty(),
+            "Can't delete store with offers or bids in escrow"
+        );
+        require!(
+            self.claimable.is_empty(),
+            "Can't delete store with balances left to withdraw"
+        );
+
         env::storage_remove(&StorageKey::StoreMetadata.try_to_vec().unwrap());
         env::storage_remove(&StorageKey::ItemsById.try_to_vec().unwrap());
         env::storage_remove(&StorageKey::ItemsMetadataById.try_to_vec().unwrap());
@@ -140,17 +335,22 @@ impl Contract {
         env::storage_remove(&StorageKey::ReviewsById.try_to_vec().unwrap());
         env::storage_remove(&StorageKey::ReviewsByAccountId.try_to_vec().unwrap());
         env::storage_remove(&StorageKey::ReviewsByItemId.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::FtTokenIds.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::StorageAccounts.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::Coupons.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::CouponUsesByAccount.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::OffersById.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::OffersByItemId.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::Balances.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::Staff.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::Collections.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::ItemsByCollectionId.try_to_vec().unwrap());
+        env::storage_remove(&StorageKey::ItemsByTag.try_to_vec().unwrap());
 
         // Emit a NearEvent
         NearEvent::store_delete().emit();
 
-        let factory_id: AccountId = env::current_account_id()
-            .as_str()
-            .split('.')
-            .collect::<Vec<&str>>()[1..]
-            .join(".")
-            .parse()
-            .unwrap();
+        let factory_id = self.internal_factory_id();
 
         // call factory to delete the store
         let cb_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
@@ -3,15 +3,42 @@
  *
  * Methods:
  * - update_store_metadata
+ * - set_confirmation_window
+ * - set_handling_time
+ * - set_return_window
+ * - store_pause
+ * - store_resume
  *
  * - store_metadata
  * - get_store_owner
  * - get_store_arbiter
+ * - get_store_treasury
+ * - get_store_commission_bps
+ * - get_confirmation_window
+ * - get_handling_time
+ * - get_return_window
+ * - get_store_pause
+ * - get_store_stats
  *
  *
  */
 use crate::*;
 
+// Store statistics, kept by the contract
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StoreStats {
+    pub late_shipments: u64, // orders refunded because they weren't shipped in time
+}
+
+// Store pause, new sales are rejected until the owner resumes or the reopening date
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorePause {
+    pub paused_at: u64,          // timestamp of the pause, in ms
+    pub reopens_at: Option<u64>, // optional, the store opens again on its own from then, in ms
+}
+
 // Store metadata
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
@@ -34,25 +61,54 @@ pub struct StoreMetadata {
     // terms values
     pub terms: Option<String>, // optional, should be a short version of the store terms
 
+    // buyers encrypt their delivery address to this key
+    pub public_key: Option<String>, // optional, ex. "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+
     // other values
     pub tags: Option<Vec<String>>, // optional, ex. ["store", "awesome"]
     pub created_at: Option<String>, // optional, timestamp of creation
     pub updated_at: Option<String>, // optional, timestamp of last update to metadata
 }
 
+// The Json Store Metadata is what will be returned from view calls.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonStoreMetadata {
+    #[serde(flatten)]
+    pub metadata: StoreMetadata,
+    pub pause: Option<StorePause>, // none if the store is open
+}
+
 pub trait StoreMetadataProvider {
-    //view call for returning the contract metadata
-    fn store_metadata(&self) -> StoreMetadata;
+    //view call for returning the contract metadata and pause
+    fn store_metadata(&self) -> JsonStoreMetadata;
     // get store owner
     fn get_store_owner(&self) -> AccountId;
     // get store arbiter
     fn get_store_arbiter(&self) -> AccountId;
+    // get the account receiving the marketplace commission
+    fn get_store_treasury(&self) -> AccountId;
+    // get the marketplace commission in basis points
+    fn get_store_commission_bps(&self) -> u16;
+    // get the time buyers have to confirm a shipped order, in ms
+    fn get_confirmation_window(&self) -> u64;
+    // get the time sellers have to ship a pending order, in ms
+    fn get_handling_time(&self) -> u64;
+    // get the time buyers have to return a completed order, in ms
+    fn get_return_window(&self) -> u64;
+    // get the store pause, none if the store is open
+    fn get_store_pause(&self) -> Option<StorePause>;
+    // get the store statistics
+    fn get_store_stats(&self) -> StoreStats;
 }
 
 #[near_bindgen]
 impl StoreMetadataProvider for Contract {
-    fn store_metadata(&self) -> StoreMetadata {
-        self.metadata.get().unwrap()
+    fn store_metadata(&self) -> JsonStoreMetadata {
+        JsonStoreMetadata {
+            metadata: self.metadata.get().unwrap(),
+            pause: self.get_store_pause(),
+        }
     }
     fn get_store_owner(&self) -> AccountId {
         self.owner_id.clone()
@@ -60,20 +116,51 @@ impl StoreMetadataProvider for Contract {
     fn get_store_arbiter(&self) -> AccountId {
         self.arbiter_id.clone()
     }
+    fn get_store_treasury(&self) -> AccountId {
+        self.treasury_id.clone()
+    }
+    fn get_store_commission_bps(&self) -> u16 {
+        self.commission_bps
+    }
+    fn get_confirmation_window(&self) -> u64 {
+        self.confirmation_window
+    }
+    fn get_handling_time(&self) -> u64 {
+        self.handling_time
+    }
+    fn get_return_window(&self) -> u64 {
+        self.return_window
+    }
+    fn get_store_pause(&self) -> Option<StorePause> {
+        self.pause.clone().filter(|_| self.internal_is_paused())
+    }
+    fn get_store_stats(&self) -> StoreStats {
+        self.stats.clone()
+    }
 }
 
 pub trait StoreMetadataManager {
     //update the contract metadata
     fn update_store_metadata(&mut self, metadata: StoreMetadata);
+    //update the time buyers have to confirm a shipped order
+    fn set_confirmation_window(&mut self, confirmation_window: u64);
+    //update the time sellers have to ship a pending order
+    fn set_handling_time(&mut self, handling_time: u64);
+    //update the time buyers have to return a completed order, 0 disables returns
+    fn set_return_window(&mut self, return_window: u64);
+    //stop new sales, optionally until a reopening date
+    fn store_pause(&mut self, reopens_at: Option<u64>);
+    //accept new sales again
+    fn store_resume(&mut self);
 }
 
 #[near_bindgen]
 impl StoreMetadataManager for Contract {
+    #[payable]
     fn update_store_metadata(&mut self, metadata: StoreMetadata) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.owner_id,
-            "Only owner can update store metadata"
+        self.internal_assert_permission(
+            Permission::Metadata,
+            "Only owner or staff can update store metadata",
         );
 
         let orders = &self.orders_by_id;
@@ -86,12 +173,20 @@ impl StoreMetadataManager for Contract {
             );
         }
 
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
         self.metadata.set(&StoreMetadata {
             created_at: self.metadata.get().unwrap().created_at,
             updated_at: Some(env::block_timestamp_ms().to_string()),
             ..metadata
         });
 
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
         // Emit NearEvent
         NearEvent::store_update(StoreUpdateData::new(
             self.owner_id.clone(),
@@ -100,6 +195,104 @@ impl StoreMetadataManager for Contract {
         ))
         .emit();
     }
+
+    fn set_confirmation_window(&mut self, confirmation_window: u64) {
+        //the window decides when buyers lose their say, only the owner sets it
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can set the confirmation window"
+        );
+        require!(
+            confirmation_window > 0,
+            "Confirmation window must be greater than 0"
+        );
+
+        //orders already shipped keep the window they were shipped with
+        self.confirmation_window = confirmation_window;
+
+        // Emit NearEvent
+        NearEvent::store_confirmation_window_update(ConfirmationWindowUpdateData::new(
+            confirmation_window,
+        ))
+        .emit();
+    }
+
+    fn set_handling_time(&mut self, handling_time: u64) {
+        self.internal_assert_permission(
+            Permission::Metadata,
+            "Only owner or staff can set the handling time",
+        );
+        require!(handling_time > 0, "Handling time must be greater than 0");
+
+        //orders already made keep the deadline they were made with
+        self.handling_time = handling_time;
+
+        // Emit NearEvent
+        NearEvent::store_handling_time_update(HandlingTimeUpdateData::new(handling_time)).emit();
+    }
+
+    fn set_return_window(&mut self, return_window: u64) {
+        //the window holds back the owner's payouts, only the owner sets it
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can set the return window"
+        );
+
+        //orders already completed keep the reserve they were completed with
+        self.return_window = return_window;
+
+        // Emit NearEvent
+        NearEvent::store_return_window_update(ReturnWindowUpdateData::new(return_window)).emit();
+    }
+
+    fn store_pause(&mut self, reopens_at: Option<u64>) {
+        self.internal_assert_permission(
+            Permission::Metadata,
+            "Only owner or staff can pause the store",
+        );
+        require!(
+            reopens_at.is_none_or(|reopens_at| reopens_at > env::block_timestamp_ms()),
+            "Reopening date must be in the future"
+        );
+
+        //existing orders are still shipped, completed, cancelled and disputed meanwhile
+        self.pause = Some(StorePause {
+            paused_at: env::block_timestamp_ms(),
+            reopens_at,
+        });
+
+        // Emit NearEvent
+        NearEvent::store_pause(StorePauseData::new(reopens_at)).emit();
+    }
+
+    fn store_resume(&mut self) {
+        self.internal_assert_permission(
+            Permission::Metadata,
+            "Only owner or staff can resume the store",
+        );
+        require!(self.pause.take().is_some(), "Store is not paused");
+
+        // Emit NearEvent
+        NearEvent::store_resume().emit();
+    }
+}
+
+impl Contract {
+    /// Whether the store is paused, a pause ends on its own at the reopening date.
+    pub(crate) fn internal_is_paused(&self) -> bool {
+        self.pause.as_ref().is_some_and(|pause| {
+            pause
+                .reopens_at
+                .is_none_or(|reopens_at| env::block_timestamp_ms() < reopens_at)
+        })
+    }
+
+    /// Panics if the store is paused, called by every path that makes a new sale.
+    pub(crate) fn internal_assert_open(&self) {
+        require!(!self.internal_is_paused(), "Store is paused");
+    }
 }
 
 // Item Metadata
@@ -0,0 +1,166 @@
+/**
+ *  Milestones
+ *
+ * Methods:
+ *
+ * - item_set_milestones
+ * - milestone_approve
+ *
+ */
+use crate::*;
+
+// Stage of a service item, ex. {"description": "First draft", "share_bps": 3000}
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ItemMilestone {
+    pub description: String,
+    pub share_bps: u16, // share of the price, the shares of an item add up to 10000
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MilestoneStatus {
+    Pending,
+    Released,
+}
+
+// Stage of an order, its amount stays in escrow until the buyer approves it
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderMilestone {
+    pub description: String,
+    pub amount: Balance,
+    pub status: MilestoneStatus,
+}
+
+pub trait MilestoneManager {
+    fn item_set_milestones(&mut self, item_id: U64, milestones: Vec<ItemMilestone>);
+    fn milestone_approve(&mut self, order_id: U64, milestone: u32);
+}
+
+#[near_bindgen]
+impl MilestoneManager for Contract {
+    #[payable]
+    fn item_set_milestones(&mut self, item_id: U64, milestones: Vec<ItemMilestone>) {
+        self.internal_assert_permission(
+            Permission::Catalog,
+            "Only owner or staff can set the milestones of an item",
+        );
+
+        let mut item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+
+        //no milestones means the item is paid at once
+        if !milestones.is_empty() {
+            require!(
+                milestones
+                    .iter()
+                    .all(|milestone| milestone.share_bps > 0 && !milestone.description.is_empty()),
+                "Milestones need a description and a share greater than 0"
+            );
+            require!(
+                milestones
+                    .iter()
+                    .map(|milestone| milestone.share_bps as u32)
+                    .sum::<u32>()
+                    == MAX_BASIS_POINTS as u32,
+                "Milestone shares must add up to 10000 basis points"
+            );
+        }
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        //orders already made keep the milestones they were made with
+        item.milestones = milestones.clone();
+        self.items_by_id.insert(&item_id.into(), &item);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::item_milestones_update(ItemMilestonesUpdateData::new(item_id, milestones))
+            .emit();
+    }
+
+    fn milestone_approve(&mut self, order_id: U64, milestone: u32) {
+        //get the order
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            order.buyer_id == env::predecessor_account_id(),
+            "Only the buyer can approve a milestone"
+        );
+        require!(
+            order.status == OrderStatus::Pending || order.status == OrderStatus::Shipped,
+            "Order is not in progress"
+        );
+
+        let pending = order
+            .milestones
+            .iter()
+            .filter(|milestone| milestone.status == MilestoneStatus::Pending)
+            .count();
+        let approved = order
+            .milestones
+            .get_mut(milestone as usize)
+            .expect("Milestone does not exist");
+        require!(
+            approved.status == MilestoneStatus::Pending,
+            "Milestone was already released"
+        );
+        approved.status = MilestoneStatus::Released;
+        let amount = approved.amount;
+
+        // Emit NearEvent
+        NearEvent::milestone_approve(MilestoneApproveData::new(order_id, milestone, U128(amount)))
+            .emit();
+
+        //the last milestone completes the order with the rest of the escrow
+        if pending == 1 {
+            return self.internal_complete_order(order_id.into(), order);
+        }
+
+        order.amount -= amount;
+        self.orders_by_id.insert(&order_id.into(), &order);
+
+        self.internal_payout_seller(order_id.into(), amount, &order.ft_token_id);
+    }
+}
+
+impl Contract {
+    /// Splits the amount of a new order of the item into its milestones.
+    pub(crate) fn internal_order_milestones(
+        &self,
+        item_id: u64,
+        amount: Balance,
+    ) -> Vec<OrderMilestone> {
+        let item = self.items_by_id.get(&item_id).expect("Item does not exist");
+        let count = item.milestones.len();
+        let mut rest = amount;
+        item.milestones
+            .into_iter()
+            .enumerate()
+            .map(|(index, milestone)| {
+                //the last milestone gets the rounding remainder
+                let milestone_amount = if index + 1 == count {
+                    rest
+                } else {
+                    amount * milestone.share_bps as u128 / MAX_BASIS_POINTS as u128
+                };
+                rest -= milestone_amount;
+                OrderMilestone {
+                    description: milestone.description,
+                    amount: milestone_amount,
+                    status: MilestoneStatus::Pending,
+                }
+            })
+            .collect()
+    }
+}
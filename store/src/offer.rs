@@ -0,0 +1,415 @@
+/**
+ *  Offers
+ *
+ * Methods:
+ *
+ * - get_offer
+ *
+ * - offer_make
+ * - offer_withdraw
+ * - offer_accept
+ * - offer_reject
+ * - offer_counter
+ * - offer_accept_counter
+ * - offer_expire
+ *
+ */
+use crate::*;
+
+/// Offers and counter-offers expire 3 days after they are made.
+pub const OFFER_DURATION: u64 = 3 * 24 * 60 * 60 * 1000;
+
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum OfferStatus {
+    Open,
+    Countered,
+    Accepted,
+    Rejected,
+    Withdrawn,
+    Expired,
+}
+
+// Offer below the listed price, the amount is held in escrow until it is closed
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Offer {
+    pub buyer_id: AccountId,
+    pub item_id: u64,
+    pub variant: Option<String>,
+    pub amount: Balance,
+    pub counter_amount: Option<Balance>, // set by the seller when countering
+    pub status: OfferStatus,
+    pub expires_at: u64,
+    pub order_id: Option<u64>,       // once accepted
+    pub storage_bytes: StorageUsage, // charged to the buyer until the offer is closed
+}
+
+pub trait OfferProvider {
+    fn get_offer(&self, offer_id: U64) -> Option<Offer>;
+}
+
+#[near_bindgen]
+impl OfferProvider for Contract {
+    fn get_offer(&self, offer_id: U64) -> Option<Offer> {
+        self.offers_by_id.get(&offer_id.into())
+    }
+}
+
+pub trait OfferManager {
+    fn offer_make(&mut self, item_id: U64, amount: U128, variant: Option<String>) -> U64;
+    fn offer_withdraw(&mut self, offer_id: U64);
+    fn offer_accept(&mut self, offer_id: U64) -> Option<U64>;
+    fn offer_reject(&mut self, offer_id: U64);
+    fn offer_counter(&mut self, offer_id: U64, amount: U128);
+    fn offer_accept_counter(&mut self, offer_id: U64) -> Option<U64>;
+    fn offer_expire(&mut self, offer_id: U64);
+}
+
+#[near_bindgen]
+impl OfferManager for Contract {
+    #[payable]
+    fn offer_make(&mut self, item_id: U64, amount: U128, variant: Option<String>) -> U64 {
+        let buyer_id = env::predecessor_account_id();
+        require!(
+            self.owner_id != buyer_id,
+            "You can't make an offer on your own item"
+        );
+        self.internal_assert_open();
+
+        //refund the offers on this item that ran out of time
+        self.internal_expire_offers(item_id.into());
+
+        //check if item is available
+        let item = self
+            .items_by_id
+            .get(&item_id.into())
+            .expect("Item does not exist");
+        require!(item.is_available(), "Item is not available");
+        require!(
+            item.stock_of(&variant) > 0,
+            "Not enough stock for this order"
+        );
+
+        //check if the offer is below the listed price
+        let amount: Balance = amount.into();
+        require!(amount > 0, "Offer must be greater than 0");
+        require!(
+            amount < item.unit_price(&variant, &None),
+            "Offer must be below the listed price, buy the item instead"
+        );
+        require!(
+            env::attached_deposit() >= amount,
+            "Not enough deposit for this offer"
+        );
+
+        //the rest of the deposit goes to the buyer's storage balance
+        let storage_deposit = env::attached_deposit() - amount;
+        if storage_deposit > 0 {
+            self.internal_storage_deposit(&buyer_id, storage_deposit);
+        }
+
+        //the offer is held in escrow until it is accepted or closed
+        self.internal_escrow_deposit(amount, &None);
+
+        let initial_storage_usage = env::storage_usage();
+
+        let offer_id = self.offers_by_id.len();
+        let expires_at = env::block_timestamp_ms() + OFFER_DURATION;
+        let mut offer = Offer {
+            buyer_id: buyer_id.clone(),
+            item_id: item_id.into(),
+            variant,
+            amount,
+            counter_amount: None,
+            status: OfferStatus::Open,
+            expires_at,
+            order_id: None,
+            storage_bytes: 0,
+        };
+        self.offers_by_id.insert(&offer_id, &offer);
+
+        //save the offer id for the item while it is open
+        let mut item_offer_ids = self
+            .offers_by_item_id
+            .get(&item_id.into())
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::OffersByItemIdInner {
+                        item_id_hash: u64::from(item_id).try_to_vec().unwrap(),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        item_offer_ids.insert(&offer_id);
+        self.offers_by_item_id
+            .insert(&item_id.into(), &item_offer_ids);
+
+        //charge the buyer for the bytes used by the offer
+        offer.storage_bytes = env::storage_usage() - initial_storage_usage;
+        self.internal_storage_charge(&buyer_id, offer.storage_bytes);
+        self.offers_by_id.insert(&offer_id, &offer);
+
+        // Emit NearEvent
+        NearEvent::offer_make(OfferMakeData::new(
+            U64(offer_id),
+            item_id,
+            buyer_id,
+            U128(amount),
+            expires_at,
+        ))
+        .emit();
+
+        U64(offer_id)
+    }
+
+    fn offer_withdraw(&mut self, offer_id: U64) {
+        let offer = self.internal_open_offer(offer_id.into());
+        require!(
+            offer.buyer_id == env::predecessor_account_id(),
+            "Only the buyer can withdraw the offer"
+        );
+
+        let refund = self.internal_close_offer(offer_id.into(), offer, OfferStatus::Withdrawn);
+
+        // Emit NearEvent
+        NearEvent::offer_withdraw(OfferWithdrawData::new(offer_id, U128(refund))).emit();
+    }
+
+    fn offer_accept(&mut self, offer_id: U64) -> Option<U64> {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can accept an offer"
+        );
+
+        let offer = self.internal_open_offer(offer_id.into());
+        require!(
+            offer.status == OfferStatus::Open,
+            "Offer was countered, wait for the buyer"
+        );
+        if self.internal_expire_offer(offer_id.into(), &offer) {
+            return None;
+        }
+
+        let amount = offer.amount;
+        let order_id = self.internal_accept_offer(offer_id.into(), offer, amount);
+        Some(U64(order_id))
+    }
+
+    fn offer_reject(&mut self, offer_id: U64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can reject an offer"
+        );
+
+        let offer = self.internal_open_offer(offer_id.into());
+        let refund = self.internal_close_offer(offer_id.into(), offer, OfferStatus::Rejected);
+
+        // Emit NearEvent
+        NearEvent::offer_reject(OfferRejectData::new(offer_id, U128(refund))).emit();
+    }
+
+    fn offer_counter(&mut self, offer_id: U64, amount: U128) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can counter an offer"
+        );
+
+        let mut offer = self.internal_open_offer(offer_id.into());
+        if self.internal_expire_offer(offer_id.into(), &offer) {
+            return;
+        }
+
+        //the counter sits between the offer and the listed price
+        let item = self
+            .items_by_id
+            .get(&offer.item_id)
+            .expect("Item does not exist");
+        let amount: Balance = amount.into();
+        require!(
+            amount > offer.amount && amount < item.unit_price(&offer.variant, &None),
+            "Counter-offer must be between the offer and the listed price"
+        );
+
+        //the buyer gets a full period to answer, the record keeps its size
+        let expires_at = env::block_timestamp_ms() + OFFER_DURATION;
+        offer.counter_amount = Some(amount);
+        offer.status = OfferStatus::Countered;
+        offer.expires_at = expires_at;
+        self.offers_by_id.insert(&offer_id.into(), &offer);
+
+        // Emit NearEvent
+        NearEvent::offer_counter(OfferCounterData::new(offer_id, U128(amount), expires_at)).emit();
+    }
+
+    #[payable]
+    fn offer_accept_counter(&mut self, offer_id: U64) -> Option<U64> {
+        self.internal_assert_open();
+        let offer = self.internal_open_offer(offer_id.into());
+        require!(
+            offer.buyer_id == env::predecessor_account_id(),
+            "Only the buyer can accept the counter-offer"
+        );
+        require!(
+            offer.status == OfferStatus::Countered,
+            "Offer was not countered"
+        );
+        if self.internal_expire_offer(offer_id.into(), &offer) {
+            //nothing was taken, return what was attached too
+            if env::attached_deposit() > 0 {
+                Promise::new(offer.buyer_id).transfer(env::attached_deposit());
+            }
+            return None;
+        }
+
+        //the buyer tops up the escrow to the counter amount
+        let amount = offer.counter_amount.unwrap();
+        let top_up = amount - offer.amount;
+        require!(
+            env::attached_deposit() >= top_up,
+            "Attach the difference with the counter-offer"
+        );
+        let storage_deposit = env::attached_deposit() - top_up;
+        if storage_deposit > 0 {
+            self.internal_storage_deposit(&offer.buyer_id, storage_deposit);
+        }
+
+        let order_id = self.internal_accept_offer(offer_id.into(), offer, amount);
+        Some(U64(order_id))
+    }
+
+    fn offer_expire(&mut self, offer_id: U64) {
+        let offer = self.internal_open_offer(offer_id.into());
+        require!(
+            self.internal_expire_offer(offer_id.into(), &offer),
+            "Offer has not expired yet"
+        );
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_open_offer(&self, offer_id: u64) -> Offer {
+        let offer = self
+            .offers_by_id
+            .get(&offer_id)
+            .expect("Offer does not exist");
+        require!(
+            offer.status == OfferStatus::Open || offer.status == OfferStatus::Countered,
+            "Offer is closed"
+        );
+        offer
+    }
+
+    /// Turns the offer into a pending order at `amount`, paid with the escrow.
+    pub(crate) fn internal_accept_offer(
+        &mut self,
+        offer_id: u64,
+        offer: Offer,
+        amount: Balance,
+    ) -> u64 {
+        let line = CartItem {
+            item_id: U64(offer.item_id),
+            quantity: None,
+            variant: offer.variant.clone(),
+            coupon: None,
+        };
+        let buyer_id = offer.buyer_id.clone();
+        self.internal_close_offer(offer_id, offer, OfferStatus::Accepted);
+
+        //the escrow is now the order's amount, nothing is refunded
+        //the buyer sets the delivery address on the order
+        let order_id = self.internal_create_order(buyer_id, line, amount, None, None, None);
+
+        let mut offer = self.offers_by_id.get(&offer_id).unwrap();
+        offer.order_id = Some(order_id);
+        self.offers_by_id.insert(&offer_id, &offer);
+
+        // Emit NearEvent
+        NearEvent::offer_accept(OfferAcceptData::new(
+            U64(offer_id),
+            U64(order_id),
+            U128(amount),
+        ))
+        .emit();
+
+        order_id
+    }
+
+    /// Closes the offer and refunds the escrow unless it was accepted, returns the refund.
+    pub(crate) fn internal_close_offer(
+        &mut self,
+        offer_id: u64,
+        mut offer: Offer,
+        status: OfferStatus,
+    ) -> Balance {
+        let refund = if status == OfferStatus::Accepted {
+            0
+        } else {
+            offer.amount
+        };
+        offer.status = status;
+        self.offers_by_id.insert(&offer_id, &offer);
+
+        if let Some(mut item_offer_ids) = self.offers_by_item_id.get(&offer.item_id) {
+            item_offer_ids.remove(&offer_id);
+            self.offers_by_item_id
+                .insert(&offer.item_id, &item_offer_ids);
+        }
+
+        //the offer is kept for history but no longer charged to the buyer
+        self.internal_storage_release(&offer.buyer_id, offer.storage_bytes);
+
+        //an accepted offer's escrow moves to its order
+        if refund > 0 {
+            self.internal_payout(offer.buyer_id, refund, &None);
+        } else {
+            self.internal_escrow_withdraw(offer.amount, &None);
+        }
+        refund
+    }
+
+    /// Refunds the offer if its time ran out, returns whether it expired.
+    pub(crate) fn internal_expire_offer(&mut self, offer_id: u64, offer: &Offer) -> bool {
+        if env::block_timestamp_ms() < offer.expires_at {
+            return false;
+        }
+
+        let offer = self.offers_by_id.get(&offer_id).unwrap();
+        let refund = self.internal_close_offer(offer_id, offer, OfferStatus::Expired);
+
+        // Emit NearEvent
+        NearEvent::offer_expire(OfferExpireData::new(U64(offer_id), U128(refund))).emit();
+        true
+    }
+
+    /// Refunds every open offer on the item whose time ran out.
+    pub(crate) fn internal_expire_offers(&mut self, item_id: u64) {
+        let offer_ids = match self.offers_by_item_id.get(&item_id) {
+            Some(item_offer_ids) => item_offer_ids.to_vec(),
+            None => return,
+        };
+        for offer_id in offer_ids {
+            let offer = self.offers_by_id.get(&offer_id).unwrap();
+            self.internal_expire_offer(offer_id, &offer);
+        }
+    }
+
+    /// Rejects and refunds every open offer on the item.
+    pub(crate) fn internal_reject_offers(&mut self, item_id: u64) {
+        let offer_ids = match self.offers_by_item_id.get(&item_id) {
+            Some(item_offer_ids) => item_offer_ids.to_vec(),
+            None => return,
+        };
+        for offer_id in offer_ids {
+            let offer = self.offers_by_id.get(&offer_id).unwrap();
+            let refund = self.internal_close_offer(offer_id, offer, OfferStatus::Rejected);
+
+            // Emit NearEvent
+            NearEvent::offer_reject(OfferRejectData::new(U64(offer_id), U128(refund))).emit();
+        }
+    }
+}
@@ -7,14 +7,20 @@
  *
  *
  * - item_buy
+ * - cart_checkout
+ * - order_shipped
+ * - order_set_delivery_address
  * - order_complete
+ * - order_finalize
  * - order_cancel
+ * - order_refund_late
+ * - order_refund
  *
  *
  */
 use crate::*;
 
-#[derive(BorshSerialize, BorshDeserialize, PartialEq, Serialize, Deserialize)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Serialize, Deserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub enum OrderStatus {
     Pending,
@@ -23,6 +29,9 @@ pub enum OrderStatus {
     Cancelled,
     Disputed,
     Resolved,
+    Returned,
+    Settling,         // waiting for the arbiter to take the dispute and its fee
+    SettlementFailed, // the arbiter call failed, see settlement_retry
 }
 
 // Order
@@ -31,11 +40,88 @@ pub enum OrderStatus {
 pub struct Order {
     pub buyer_id: AccountId,
     pub item_id: u64,
+    // key of the bought variant, if the item has any
+    pub variant: Option<String>,
+    pub quantity: u64,
     pub amount: Balance,
+    // None when paid in NEAR
+    pub ft_token_id: Option<AccountId>,
     pub status: OrderStatus,
     pub starts: u64,
+    // the buyer can get a refund after this
+    pub ship_by: u64,
+    // set by the seller when shipping
+    pub shipped_at: Option<u64>,
+    // anyone can finalize from then
+    pub finalize_after: Option<u64>,
     pub ends: Option<u64>,
     pub dispute_id: Option<u64>,
+    // charged to the buyer until the order is finished
+    pub storage_bytes: StorageUsage,
+    // encrypted to the store's public key
+    pub delivery_address: Option<String>,
+    // set by the seller when shipping
+    pub shipping: Option<ShippingDetails>,
+    // partial refunds, taken out of `amount`
+    pub refunds: Vec<OrderRefund>,
+    // seller's payout is held until then for returns
+    pub reserve_until: Option<u64>,
+    // set when the buyer asks for a return
+    pub return_request: Option<OrderReturn>,
+    // `amount` is what is left of the unreleased ones
+    pub milestones: Vec<OrderMilestone>,
+    // set while a dispute is being created
+    pub settlement: Option<Settlement>,
+    // id of the first order of its cart
+    pub cart_id: Option<u64>,
+    // applied coupon, `amount` is already discounted
+    pub coupon: Option<String>,
+}
+
+impl Order {
+    /// Takes `amount` out of the escrow, from the last milestones not released yet.
+    pub fn deduct(&mut self, amount: Balance) {
+        self.amount -= amount;
+        let mut rest = amount;
+        for milestone in self
+            .milestones
+            .iter_mut()
+            .rev()
+            .filter(|milestone| milestone.status == MilestoneStatus::Pending)
+        {
+            let cut = rest.min(milestone.amount);
+            milestone.amount -= cut;
+            rest -= cut;
+        }
+    }
+}
+
+// Shipping details, ex. {"carrier": "DHL", "tracking_number": "JD0002", "expected_delivery": 1672531200000}
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ShippingDetails {
+    pub carrier: String,                // required
+    pub tracking_number: String,        // required
+    pub expected_delivery: Option<u64>, // optional, timestamp in ms
+}
+
+// Partial refund made by the seller
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderRefund {
+    pub amount: Balance,
+    pub reason: Option<String>,
+    pub created_at: u64,
+}
+
+// Line of a cart, ex. {"item_id": "0", "quantity": 2, "variant": "xl", "coupon": "SUMMER10"}
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CartItem {
+    pub item_id: U64,
+    pub quantity: Option<u64>,
+    pub variant: Option<String>,
+    pub coupon: Option<String>,
 }
 
 pub trait OrderProvider {
@@ -52,16 +138,39 @@ impl OrderProvider for Contract {
 // Order Actions
 
 pub trait OrderActions {
-    fn item_buy(&mut self, item_id: U64) -> U64;
-    fn order_complete(&mut self, order_id: U64) -> Promise;
-    fn order_cancel(&mut self, order_id: U64) -> Promise;
-    fn order_shipped(&mut self, order_id: U64);
+    fn item_buy(
+        &mut self,
+        item_id: U64,
+        quantity: Option<u64>,
+        variant: Option<String>,
+        coupon: Option<String>,
+        delivery_address: Option<String>,
+    ) -> U64;
+    fn cart_checkout(&mut self, items: Vec<CartItem>, delivery_address: Option<String>)
+        -> Vec<U64>;
+    fn order_complete(&mut self, order_id: U64);
+    fn order_finalize(&mut self, order_id: U64);
+    fn order_cancel(&mut self, order_id: U64);
+    fn order_refund_late(&mut self, order_id: U64);
+    fn order_refund(&mut self, order_id: U64, amount: U128, reason: Option<String>);
+    fn order_shipped(&mut self, order_id: U64, shipping: ShippingDetails);
+    fn order_set_delivery_address(&mut self, order_id: U64, delivery_address: String);
 }
 
 #[near_bindgen]
 impl OrderActions for Contract {
     #[payable]
-    fn item_buy(&mut self, item_id: U64) -> U64 {
+    fn item_buy(
+        &mut self,
+        item_id: U64,
+        quantity: Option<u64>,
+        variant: Option<String>,
+        coupon: Option<String>,
+        delivery_address: Option<String>,
+    ) -> U64 {
+        //check if the store is open
+        self.internal_assert_open();
+
         //check if item exists
         require!(
             self.items_by_id.contains_key(&item_id.into()),
@@ -70,11 +179,24 @@ impl OrderActions for Contract {
 
         //check if item is available
         let item = self.items_by_id.get(&item_id.into()).unwrap();
-        require!(item.status == ItemStatus::Active, "Item is not available");
-
-        //check if attached item price + storage cost (0.01 NEAR)
+        require!(item.is_available(), "Item is not available");
+
+        //check if attached item price, or the variant's, for the quantity
+        let quantity = quantity.unwrap_or(1);
+        let mut amount = item.unit_price(&variant, &None) * quantity as u128;
+
+        //apply the coupon, if any
+        if let Some(code) = &coupon {
+            amount -= self.internal_coupon_discount(
+                code,
+                &env::predecessor_account_id(),
+                item_id.into(),
+                amount,
+                &None,
+            );
+        }
         require!(
-            env::attached_deposit() >= item.price + 10_000_000_000_000_000_000_000,
+            env::attached_deposit() >= amount,
             "Not enough deposit to buy this item"
         );
 
@@ -84,69 +206,132 @@ impl OrderActions for Contract {
             "You can't buy your own item"
         );
 
-        //create the order
-        let order_id = self.orders_by_id.len().into();
-        let order = Order {
-            buyer_id: env::predecessor_account_id(),
-            item_id: item_id.into(),
-            amount: item.price,
-            status: OrderStatus::Pending,
-            starts: env::block_timestamp_ms(),
-            ends: None,
-            dispute_id: None,
-        };
-        
-        //save the order
-        self.orders_by_id.insert(&order_id, &order);
+        //the rest of the deposit goes to the buyer's storage balance
+        let storage_deposit = env::attached_deposit() - amount;
+        if storage_deposit > 0 {
+            self.internal_storage_deposit(&env::predecessor_account_id(), storage_deposit);
+        }
 
-        //save the order id for the buyer
-        let mut buyer_order_ids = self
-            .orders_by_account_id
-            .get(&env::predecessor_account_id())
-            .unwrap_or_else(|| {
-                UnorderedSet::new(
-                    StorageKey::OrdersByAccountIdInner {
-                        account_id_hash: env::predecessor_account_id().try_to_vec().unwrap(),
-                    }
-                    .try_to_vec()
-                    .unwrap(),
-                )
-            });
-        buyer_order_ids.insert(&order_id);
-        self.orders_by_account_id
-            .insert(&env::predecessor_account_id(), &buyer_order_ids);
+        let order_id = self.internal_create_order(
+            env::predecessor_account_id(),
+            CartItem {
+                item_id,
+                quantity: Some(quantity),
+                variant,
+                coupon,
+            },
+            amount,
+            None,
+            None,
+            delivery_address,
+        );
 
-        //save the order id for the item
-        let mut item_order_ids = self
-            .orders_by_item_id
-            .get(&item_id.into())
-            .unwrap_or_else(|| {
-                UnorderedSet::new(
-                    StorageKey::OrdersByItemIdInner {
-                        item_id_hash: item_id.try_to_vec().unwrap(),
-                    }
-                    .try_to_vec()
-                    .unwrap(),
-                )
-            });
-        item_order_ids.insert(&order_id);
-        self.orders_by_item_id
-            .insert(&item_id.into(), &item_order_ids);
+        //return the order id
+        U64(order_id)
+    }
+
+    #[payable]
+    fn cart_checkout(
+        &mut self,
+        items: Vec<CartItem>,
+        delivery_address: Option<String>,
+    ) -> Vec<U64> {
+        let buyer_id = env::predecessor_account_id();
+        self.internal_assert_open();
+        require!(!items.is_empty(), "Cart is empty");
+
+        // can't buy your own items
+        require!(self.owner_id != buyer_id, "You can't buy your own item");
+
+        //check every line before creating any order
+        let mut amounts = Vec::with_capacity(items.len());
+        let mut reserved: HashMap<(u64, Option<String>), u64> = HashMap::new();
+        for line in items.iter() {
+            let item = self
+                .items_by_id
+                .get(&line.item_id.into())
+                .expect("Item does not exist");
+            require!(item.is_available(), "Item is not available");
+
+            let quantity = line.quantity.unwrap_or(1);
+            require!(quantity > 0, "Quantity must be greater than 0");
+
+            //the same item can be in several lines
+            let units = reserved
+                .entry((line.item_id.into(), line.variant.clone()))
+                .or_insert(0);
+            *units += quantity;
+            require!(
+                item.stock_of(&line.variant) >= *units,
+                "Not enough stock for this order"
+            );
+
+            let mut amount = item.unit_price(&line.variant, &None) * quantity as u128;
+            if let Some(code) = &line.coupon {
+                amount -= self.internal_coupon_discount(
+                    code,
+                    &buyer_id,
+                    line.item_id.into(),
+                    amount,
+                    &None,
+                );
+            }
+            amounts.push(amount);
+        }
+
+        //check if attached the price of the whole cart
+        let amount: Balance = amounts.iter().sum();
+        require!(
+            env::attached_deposit() >= amount,
+            "Not enough deposit to checkout the cart"
+        );
+
+        //the rest of the deposit covers the storage of the orders
+        let overpayment = env::attached_deposit() - amount;
+        if overpayment > 0 {
+            self.internal_storage_deposit(&buyer_id, overpayment);
+        }
+
+        //the orders are linked by the id of the first one
+        let cart_id = self.orders_by_id.len();
+        let order_ids: Vec<U64> = items
+            .into_iter()
+            .zip(amounts)
+            .map(|(line, amount)| {
+                U64(self.internal_create_order(
+                    buyer_id.clone(),
+                    line,
+                    amount,
+                    None,
+                    Some(cart_id),
+                    delivery_address.clone(),
+                ))
+            })
+            .collect();
+
+        //refund what the orders' storage didn't use
+        let mut account = self.storage_accounts.get(&buyer_id).unwrap();
+        let refund = overpayment.min(self.internal_storage_balance(&account).available.0);
+        if refund > 0 {
+            account.deposit -= refund;
+            self.storage_accounts.insert(&buyer_id, &account);
+            Promise::new(buyer_id.clone()).transfer(refund);
+        }
 
         // Emit NearEvent
-        NearEvent::item_buy(ItemBuyData::new(
-            item_id,
-            env::predecessor_account_id(),
-            U128(item.price),
-            U64(order_id),
+        NearEvent::cart_checkout(CartCheckoutData::new(
+            U64(cart_id),
+            buyer_id,
+            order_ids.clone(),
+            U128(amount),
+            U128(refund),
         ))
         .emit();
 
-        //return the order id
-        U64(order_id)
+        order_ids
     }
 
-    fn order_shipped(&mut self, order_id: U64) {
+    fn order_shipped(&mut self, order_id: U64, shipping: ShippingDetails) {
         //check if order is pending
         let order = self.orders_by_id.get(&order_id.into()).unwrap();
         require!(
@@ -154,24 +339,60 @@ impl OrderActions for Contract {
             "Order is not pending status"
         );
 
-        //check if the caller is the owner
-        require!(
-            self.owner_id == env::predecessor_account_id(),
-            "Only the owner can ship the order"
+        //check if the caller is the owner or staff
+        self.internal_assert_permission(
+            Permission::Shipping,
+            "Only the owner or staff can ship the order",
         );
 
         //update the order status
         let mut order = self.orders_by_id.get(&order_id.into()).unwrap();
         order.status = OrderStatus::Shipped;
+        order.shipped_at = Some(env::block_timestamp_ms());
+        //the buyer's window is fixed now, later changes to the store's window don't apply
+        order.finalize_after = Some(env::block_timestamp_ms() + self.confirmation_window);
+
+        //the shipping details are the seller's, their bytes are charged to the owner
+        let initial_storage_usage = env::storage_usage();
+        order.shipping = Some(shipping.clone());
         self.orders_by_id.insert(&order_id.into(), &order);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
 
         // Emit NearEvent
-        NearEvent::order_shipped(OrderShippedData::new(order_id)).emit();
+        NearEvent::order_shipped(OrderShippedData::new(order_id, shipping)).emit();
+    }
+
+    fn order_set_delivery_address(&mut self, order_id: U64, delivery_address: String) {
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            order.buyer_id == env::predecessor_account_id(),
+            "Only the buyer can set the delivery address"
+        );
+        require!(
+            order.status == OrderStatus::Pending,
+            "Order is not pending status"
+        );
+
+        //the buyer pays for the address like for the rest of the order
+        let initial_storage_usage = env::storage_usage();
+        order.delivery_address = Some(delivery_address);
+        self.orders_by_id.insert(&order_id.into(), &order);
+        let storage_usage = env::storage_usage();
+        if storage_usage > initial_storage_usage {
+            order.storage_bytes += storage_usage - initial_storage_usage;
+        } else {
+            order.storage_bytes -= initial_storage_usage - storage_usage;
+        }
+        self.orders_by_id.insert(&order_id.into(), &order);
+        self.internal_storage_update(&order.buyer_id, initial_storage_usage);
     }
 
-    fn order_complete(&mut self, order_id: U64) -> Promise {
+    fn order_complete(&mut self, order_id: U64) {
         //get the order
-        let mut order = self.orders_by_id.get(&order_id.into()).unwrap();
+        let order = self.orders_by_id.get(&order_id.into()).unwrap();
 
         //check if order is shipped
         require!(
@@ -188,48 +409,319 @@ impl OrderActions for Contract {
             "Only the buyer can complete the order"
         );
 
-        //update the order status
-        order.status = OrderStatus::Completed;
+        self.internal_complete_order(order_id.into(), order)
+    }
 
-        //save the order
-        self.orders_by_id.insert(&order_id.into(), &order);
+    fn order_finalize(&mut self, order_id: U64) {
+        //get the order
+        let order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
 
-        // Emit NearEvent
-        NearEvent::order_complete(OrderCompleteData::new(order_id)).emit();
+        //disputed orders are no longer shipped, the arbiter settles them
+        require!(
+            order.status == OrderStatus::Shipped,
+            "Order is not shipped yet"
+        );
+
+        //anyone can finalize once the buyer had the whole window to confirm
+        let finalize_after = order.finalize_after.expect("Order is not shipped yet");
+        require!(
+            env::block_timestamp_ms() >= finalize_after,
+            "Confirmation window has not passed yet"
+        );
 
-        //transfer the amount to the seller
-        Promise::new(self.owner_id.to_owned()).transfer(order.amount)
+        self.internal_complete_order(order_id.into(), order)
     }
 
-    fn order_cancel(&mut self, order_id: U64) -> Promise {
+    fn order_cancel(&mut self, order_id: U64) {
         //get the order
-        let mut order = self.orders_by_id.get(&order_id.into()).unwrap();
+        let order = self.orders_by_id.get(&order_id.into()).unwrap();
 
         //get the caller id
         let caller_id = env::predecessor_account_id();
+        let seller = self.internal_has_permission(&caller_id, Permission::Cancel);
 
-        // owner can cancel Shipped and Pending orders
-        // buyer can cancel only Pending orders
+        // owner and staff can cancel Shipped and Pending orders
+        // buyer can cancel only Pending orders, a milestone order in progress is disputed instead
+        let buyer = caller_id == order.buyer_id && order.milestones.is_empty();
         require!(
-            (order.status == OrderStatus::Pending
-                && (caller_id == self.owner_id || caller_id == order.buyer_id))
-                || (order.status == OrderStatus::Shipped && caller_id == self.owner_id),
+            (order.status == OrderStatus::Pending && (seller || buyer))
+                || (order.status == OrderStatus::Shipped && seller),
             "Order cannot be cancelled at this stage"
         );
 
+        // Emit NearEvent
+        NearEvent::order_cancel(OrderCancelData::new(order_id)).emit();
+
+        self.internal_cancel_order(order_id.into(), order)
+    }
+
+    fn order_refund_late(&mut self, order_id: U64) {
+        //get the order
+        let order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+
+        //anyone can refund an order the seller didn't ship in time
+        require!(
+            order.status == OrderStatus::Pending,
+            "Order is not pending status"
+        );
+        require!(
+            env::block_timestamp_ms() > order.ship_by,
+            "Handling time has not passed yet"
+        );
+        //milestone orders are delivered in stages, the buyer disputes them instead
+        require!(
+            order.milestones.is_empty(),
+            "Milestone orders can't be refunded late"
+        );
+
+        //counts against the seller
+        self.stats.late_shipments += 1;
+
+        // Emit NearEvent
+        NearEvent::order_late_refund(OrderLateRefundData::new(
+            order_id,
+            order.buyer_id.clone(),
+            U128(order.amount),
+        ))
+        .emit();
+
+        self.internal_cancel_order(order_id.into(), order)
+    }
+
+    fn order_refund(&mut self, order_id: U64, amount: U128, reason: Option<String>) {
+        //check if the caller is the owner or staff
+        self.internal_assert_permission(
+            Permission::Cancel,
+            "Only the owner or staff can refund the order",
+        );
+
+        //get the order
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            order.status == OrderStatus::Pending || order.status == OrderStatus::Shipped,
+            "Order cannot be refunded at this stage"
+        );
+
+        //a full refund is a cancellation
+        let amount: Balance = amount.into();
+        require!(
+            amount > 0 && amount < order.amount,
+            "Refund must be less than the order amount, cancel the order instead"
+        );
+
+        //the history is the seller's, its bytes are charged to the owner
+        let initial_storage_usage = env::storage_usage();
+        //the refund comes out of the last milestones not released yet
+        order.deduct(amount);
+        order.refunds.push(OrderRefund {
+            amount,
+            reason: reason.clone(),
+            created_at: env::block_timestamp_ms(),
+        });
+        self.orders_by_id.insert(&order_id.into(), &order);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::order_refund(OrderRefundData::new(
+            order_id,
+            U128(amount),
+            U128(order.amount),
+            reason,
+        ))
+        .emit();
+
+        //the rest stays in escrow
+        self.internal_payout(order.buyer_id, amount, &order.ft_token_id);
+    }
+}
+
+impl Contract {
+    /// Cancels an order, returns its units to the item and refunds the buyer.
+    pub(crate) fn internal_cancel_order(&mut self, order_id: u64, mut order: Order) {
         //update the order status
         order.status = OrderStatus::Cancelled;
 
         //save the order
-        self.orders_by_id.insert(&order_id.into(), &order);
+        self.orders_by_id.insert(&order_id, &order);
 
-        //get the buyer id
-        let buyer_id = order.buyer_id;
+        //the order is kept for history but no longer charged to the buyer
+        self.internal_storage_release(&order.buyer_id, order.storage_bytes);
 
-        // Emit NearEvent
-        NearEvent::order_cancel(OrderCancelData::new(order_id)).emit();
+        //the reserved units go back to the item
+        self.internal_return_stock(order.item_id, &order.variant, order.quantity);
 
         //refund the amount to the buyer
-        Promise::new(buyer_id).transfer(order.amount)
+        self.internal_payout(order.buyer_id, order.amount, &order.ft_token_id);
+    }
+
+    /// Completes a shipped order and pays the seller, minus the marketplace commission.
+    /// With a return window the payout is held back as the return reserve instead.
+    pub(crate) fn internal_complete_order(&mut self, order_id: u64, mut order: Order) {
+        //update the order status, completing releases every milestone left
+        order.status = OrderStatus::Completed;
+        for milestone in order.milestones.iter_mut() {
+            milestone.status = MilestoneStatus::Released;
+        }
+        if self.return_window > 0 {
+            order.reserve_until = Some(env::block_timestamp_ms() + self.return_window);
+        }
+
+        //save the order
+        self.orders_by_id.insert(&order_id, &order);
+
+        // Emit NearEvent
+        NearEvent::order_complete(OrderCompleteData::new(U64(order_id))).emit();
+
+        //the reserve is released with return_reserve_release, the order is charged until then
+        if order.reserve_until.is_some() {
+            return;
+        }
+
+        //the order is kept for history but no longer charged to the buyer
+        self.internal_storage_release(&order.buyer_id, order.storage_bytes);
+
+        //credit the amount to the seller, minus the marketplace commission
+        self.internal_payout_seller(order_id, order.amount, &order.ft_token_id);
+    }
+
+    /// Creates a pending order, indexes it for the buyer and the item and emits `item_buy`.
+    /// The item's stock is reserved, the amount is held in escrow and the buyer must have enough
+    /// storage balance to cover the order.
+    pub(crate) fn internal_create_order(
+        &mut self,
+        buyer_id: AccountId,
+        line: CartItem,
+        amount: Balance,
+        ft_token_id: Option<AccountId>,
+        cart_id: Option<u64>,
+        delivery_address: Option<String>,
+    ) -> u64 {
+        self.internal_reserve_stock(
+            line.item_id.into(),
+            &line.variant,
+            line.quantity.unwrap_or(1),
+            &buyer_id,
+        );
+        self.internal_escrow_deposit(amount, &ft_token_id);
+        let order_id = self.internal_insert_order(
+            buyer_id.clone(),
+            line,
+            amount,
+            ft_token_id,
+            cart_id,
+            delivery_address,
+        );
+
+        //charge the buyer for the bytes used by the order
+        let storage_bytes = self.orders_by_id.get(&order_id).unwrap().storage_bytes;
+        self.internal_storage_charge(&buyer_id, storage_bytes);
+
+        order_id
+    }
+
+    /// Saves and indexes a pending order and emits `item_buy`, without touching the stock
+    /// nor charging its bytes, which are set on the order for the caller to charge.
+    pub(crate) fn internal_insert_order(
+        &mut self,
+        buyer_id: AccountId,
+        line: CartItem,
+        amount: Balance,
+        ft_token_id: Option<AccountId>,
+        cart_id: Option<u64>,
+        delivery_address: Option<String>,
+    ) -> u64 {
+        let CartItem {
+            item_id,
+            quantity,
+            variant,
+            coupon,
+        } = line;
+        let item_id: u64 = item_id.into();
+        let quantity = quantity.unwrap_or(1);
+
+        let initial_storage_usage = env::storage_usage();
+
+        //record the coupon use, its caps are checked again for carts using it several times
+        if let Some(code) = &coupon {
+            self.internal_use_coupon(code, &buyer_id);
+        }
+
+        //create the order
+        let order_id = self.orders_by_id.len();
+        let mut order = Order {
+            buyer_id: buyer_id.clone(),
+            item_id,
+            variant,
+            quantity,
+            amount,
+            ft_token_id,
+            status: OrderStatus::Pending,
+            starts: env::block_timestamp_ms(),
+            ship_by: env::block_timestamp_ms() + self.handling_time,
+            shipped_at: None,
+            finalize_after: None,
+            ends: None,
+            dispute_id: None,
+            storage_bytes: 0,
+            cart_id,
+            coupon,
+            delivery_address,
+            shipping: None,
+            refunds: Vec::new(),
+            reserve_until: None,
+            return_request: None,
+            milestones: self.internal_order_milestones(item_id, amount),
+            settlement: None,
+        };
+
+        //save the order
+        self.orders_by_id.insert(&order_id, &order);
+
+        //save the order id for the buyer
+        let mut buyer_order_ids = self.orders_by_account_id.get(&buyer_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::OrdersByAccountIdInner {
+                    account_id_hash: buyer_id.try_to_vec().unwrap(),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        buyer_order_ids.insert(&order_id);
+        self.orders_by_account_id.insert(&buyer_id, &buyer_order_ids);
+
+        //save the order id for the item
+        let mut item_order_ids = self.orders_by_item_id.get(&item_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                StorageKey::OrdersByItemIdInner {
+                    item_id_hash: item_id.try_to_vec().unwrap(),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        item_order_ids.insert(&order_id);
+        self.orders_by_item_id.insert(&item_id, &item_order_ids);
+
+        //the bytes used by the order are the buyer's
+        order.storage_bytes = env::storage_usage() - initial_storage_usage;
+        self.orders_by_id.insert(&order_id, &order);
+
+        // Emit NearEvent, the buyer may have paid with a token transfer
+        let buyer_id = order.buyer_id.clone();
+        NearEvent::item_buy(ItemBuyData::new(U64(order_id), order))
+            .actor(buyer_id)
+            .emit();
+
+        order_id
     }
 }
@@ -0,0 +1,192 @@
+/**
+ *  Ownership
+ *
+ * Methods:
+ *
+ * - get_pending_owner
+ *
+ * - ownership_propose
+ * - ownership_cancel
+ * - ownership_accept
+ * - ownership_sync
+ * - ownership_sync_callback
+ *
+ */
+use crate::*;
+
+pub trait OwnershipProvider {
+    fn get_pending_owner(&self) -> Option<AccountId>;
+}
+
+#[near_bindgen]
+impl OwnershipProvider for Contract {
+    fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner_id.clone()
+    }
+}
+
+pub trait OwnershipManager {
+    fn ownership_propose(&mut self, new_owner_id: AccountId);
+    fn ownership_cancel(&mut self);
+    fn ownership_accept(&mut self) -> Promise;
+    fn ownership_sync(&mut self) -> Promise;
+    fn ownership_sync_callback(&mut self, owner_id: AccountId);
+}
+
+#[near_bindgen]
+impl OwnershipManager for Contract {
+    fn ownership_propose(&mut self, new_owner_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can transfer the store"
+        );
+        require!(
+            new_owner_id != self.owner_id,
+            "New owner must be another account"
+        );
+        self.internal_assert_no_active_orders();
+
+        //a new proposal replaces the previous one
+        self.pending_owner_id = Some(new_owner_id.clone());
+
+        // Emit NearEvent
+        NearEvent::ownership_propose(OwnershipProposeData::new(
+            self.owner_id.clone(),
+            new_owner_id,
+        ))
+        .emit();
+    }
+
+    fn ownership_cancel(&mut self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can cancel the transfer"
+        );
+        require!(
+            self.pending_owner_id.take().is_some(),
+            "No transfer was proposed"
+        );
+
+        // Emit NearEvent
+        NearEvent::ownership_cancel().emit();
+    }
+
+    #[payable]
+    fn ownership_accept(&mut self) -> Promise {
+        let new_owner_id = env::predecessor_account_id();
+        require!(
+            self.pending_owner_id.as_ref() == Some(&new_owner_id),
+            "Only the proposed owner can accept the transfer"
+        );
+        //orders made since the proposal still block it
+        self.internal_assert_no_active_orders();
+
+        //the attached deposit goes to the new owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&new_owner_id, env::attached_deposit());
+        }
+
+        let old_owner_id = self.owner_id.clone();
+
+        //the staff were chosen by the previous owner, the new owner grants the roles again
+        let initial_storage_usage = env::storage_usage();
+        let staff_ids = self.staff.keys_as_vector().to_vec();
+        self.staff.clear();
+        self.internal_storage_update(&old_owner_id, initial_storage_usage);
+        for account_id in staff_ids {
+            // Emit NearEvent
+            NearEvent::staff_revoke(StaffRevokeData::new(account_id)).emit();
+        }
+
+        self.owner_id = new_owner_id.clone();
+        self.pending_owner_id = None;
+
+        //the store's bytes move to the new owner, who must have the storage balance for them
+        let used_bytes = self
+            .storage_accounts
+            .get(&old_owner_id)
+            .map_or(0, |account| account.used_bytes);
+        self.internal_storage_release(&old_owner_id, used_bytes);
+        if used_bytes > 0 {
+            self.internal_storage_charge(&new_owner_id, used_bytes);
+        }
+
+        // Emit NearEvent
+        NearEvent::ownership_transfer(OwnershipTransferData::new(old_owner_id, new_owner_id))
+            .emit();
+
+        //the factory lists the store under the new owner, ownership_sync retries if it fails
+        self.internal_sync_factory()
+    }
+
+    fn ownership_sync(&mut self) -> Promise {
+        //anyone can retry it, the factory only follows the store's owner
+        require!(
+            self.factory_owner_id != self.owner_id,
+            "Factory already lists the store under its owner"
+        );
+
+        self.internal_sync_factory()
+    }
+
+    #[private]
+    fn ownership_sync_callback(&mut self, owner_id: AccountId) {
+        if near_sdk::is_promise_success() {
+            self.factory_owner_id = owner_id;
+        }
+    }
+}
+
+impl Contract {
+    /// Factory that created the store, the parent account.
+    pub(crate) fn internal_factory_id(&self) -> AccountId {
+        env::current_account_id()
+            .as_str()
+            .split('.')
+            .collect::<Vec<&str>>()[1..]
+            .join(".")
+            .parse()
+            .unwrap()
+    }
+
+    /// Asks the factory to move the store from the owner it has on record to the current one.
+    fn internal_sync_factory(&self) -> Promise {
+        let args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "store_id": env::current_account_id(),
+            "old_owner_id": self.factory_owner_id,
+            "new_owner_id": self.owner_id,
+        }))
+        .unwrap();
+
+        let callback_args: Vec<u8> = near_sdk::serde_json::to_vec(&near_sdk::serde_json::json!({
+            "owner_id": self.owner_id,
+        }))
+        .expect("Failed to serialize callback args");
+
+        Promise::new(self.internal_factory_id())
+            .function_call("transfer_store".to_string(), args, 0, Gas::ONE_TERA * 5)
+            .then(Promise::new(env::current_account_id()).function_call(
+                "ownership_sync_callback".to_string(),
+                callback_args,
+                0,
+                Gas::ONE_TERA * 5,
+            ))
+    }
+
+    /// Panics if an order still holds funds of the current owner's customers.
+    fn internal_assert_no_active_orders(&self) {
+        for order in self.orders_by_id.values() {
+            require!(
+                order.status != OrderStatus::Pending
+                    && order.status != OrderStatus::Shipped
+                    && order.status != OrderStatus::Disputed
+                    && order.status != OrderStatus::Settling
+                    && order.status != OrderStatus::SettlementFailed
+                    && order.reserve_until.is_none(),
+                "Can't transfer the store while there are active orders or disputes"
+            );
+        }
+    }
+}
@@ -0,0 +1,184 @@
+/**
+ *  Payment
+ *
+ * Methods:
+ *
+ * - get_ft_tokens
+ *
+ * - ft_token_add
+ * - ft_token_remove
+ *
+ * - ft_on_transfer
+ *
+ */
+use crate::*;
+
+// Message expected in `ft_transfer_call` to buy an item, ex. {"item_id": "0", "quantity": 2, "coupon": "SUMMER10"}
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FtBuyMsg {
+    pub item_id: U64,
+    pub quantity: Option<u64>,
+    pub variant: Option<String>,
+    pub coupon: Option<String>,
+    pub delivery_address: Option<String>,
+}
+
+pub trait FtTokenProvider {
+    fn get_ft_tokens(&self) -> Vec<AccountId>;
+}
+
+#[near_bindgen]
+impl FtTokenProvider for Contract {
+    fn get_ft_tokens(&self) -> Vec<AccountId> {
+        self.ft_token_ids.to_vec()
+    }
+}
+
+pub trait FtTokenManager {
+    fn ft_token_add(&mut self, ft_token_id: AccountId);
+    fn ft_token_remove(&mut self, ft_token_id: AccountId);
+}
+
+#[near_bindgen]
+impl FtTokenManager for Contract {
+    #[payable]
+    fn ft_token_add(&mut self, ft_token_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can add accepted tokens"
+        );
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+        self.ft_token_ids.insert(&ft_token_id);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+    }
+
+    fn ft_token_remove(&mut self, ft_token_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can remove accepted tokens"
+        );
+        let initial_storage_usage = env::storage_usage();
+        self.ft_token_ids.remove(&ft_token_id);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+    }
+}
+
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for Contract {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        //the token contract is the caller
+        let ft_token_id = env::predecessor_account_id();
+
+        let FtBuyMsg {
+            item_id,
+            quantity,
+            variant,
+            coupon,
+            delivery_address,
+        } = near_sdk::serde_json::from_str(&msg)
+            .expect("Invalid msg, expected {\"item_id\": \"<id>\"}");
+
+        //check if the store is open, the tokens go back to the sender otherwise
+        self.internal_assert_open();
+
+        //check if item exists
+        require!(
+            self.items_by_id.contains_key(&item_id.into()),
+            "Item does not exist"
+        );
+
+        //check if item is available
+        let item = self.items_by_id.get(&item_id.into()).unwrap();
+        require!(item.is_available(), "Item is not available");
+
+        //check if the store accepts this token
+        require!(
+            self.ft_token_ids.contains(&ft_token_id),
+            "Token is not accepted by this store"
+        );
+
+        //check if item, or the variant, is priced in this token
+        let quantity = quantity.unwrap_or(1);
+        let ft_token = Some(ft_token_id.clone());
+        let mut price = item.unit_price(&variant, &ft_token) * quantity as u128;
+
+        //apply the coupon, if any
+        if let Some(code) = &coupon {
+            price -=
+                self.internal_coupon_discount(code, &sender_id, item_id.into(), price, &ft_token);
+        }
+
+        //check if enough tokens were sent
+        require!(amount.0 >= price, "Not enough tokens to buy this item");
+
+        // can't buy your own item
+        require!(self.owner_id != sender_id, "You can't buy your own item");
+
+        self.internal_create_order(
+            sender_id,
+            CartItem {
+                item_id,
+                quantity: Some(quantity),
+                variant,
+                coupon,
+            },
+            price,
+            Some(ft_token_id),
+            None,
+            delivery_address,
+        );
+
+        //the token contract refunds whatever is returned as unused
+        PromiseOrValue::Value(U128(amount.0 - price))
+    }
+}
+
+impl Contract {
+    /// Pays the seller for an order, sending the marketplace commission to the treasury.
+    pub(crate) fn internal_payout_seller(
+        &mut self,
+        order_id: u64,
+        amount: Balance,
+        ft_token_id: &Option<AccountId>,
+    ) {
+        let commission = amount * self.commission_bps as u128 / MAX_BASIS_POINTS as u128;
+        self.internal_payout(self.owner_id.clone(), amount - commission, ft_token_id);
+
+        if commission == 0 {
+            return;
+        }
+
+        // Emit NearEvent
+        NearEvent::order_commission(OrderCommissionData::new(
+            U64(order_id),
+            self.treasury_id.clone(),
+            U128(commission),
+            ft_token_id.clone(),
+        ))
+        .emit();
+
+        self.internal_payout(self.treasury_id.clone(), commission, ft_token_id);
+    }
+}
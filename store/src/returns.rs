@@ -0,0 +1,215 @@
+/**
+ *  Returns
+ *
+ * Methods:
+ *
+ * - return_request
+ * - return_accept
+ * - return_decline
+ * - return_reserve_release
+ *
+ */
+use crate::*;
+
+/// Sellers have 3 days to accept or decline a return before the buyer can escalate it.
+pub const RETURN_RESPONSE_TIME: u64 = 3 * 24 * 60 * 60 * 1000;
+
+/// Buyers have at least 3 days to dispute a declined return before the reserve can be released.
+pub const RETURN_DISPUTE_TIME: u64 = 3 * 24 * 60 * 60 * 1000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ReturnStatus {
+    Requested, // the buyer can dispute it once the seller is late
+    Accepted,  // the buyer was refunded and sends the item back
+    Declined,  // the buyer can still dispute it while the reserve is held
+}
+
+// Return requested by the buyer on a completed order
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OrderReturn {
+    pub reason: String,
+    pub status: ReturnStatus,
+    pub requested_at: u64,
+    pub respond_by: u64, // the seller accepts or declines it before this
+    pub shipment: Option<ShippingDetails>, // how to send the item back, set by the seller when accepting
+}
+
+pub trait ReturnManager {
+    fn return_request(&mut self, order_id: U64, reason: String);
+    fn return_accept(&mut self, order_id: U64, shipment: ShippingDetails);
+    fn return_decline(&mut self, order_id: U64);
+    fn return_reserve_release(&mut self, order_id: U64);
+}
+
+#[near_bindgen]
+impl ReturnManager for Contract {
+    fn return_request(&mut self, order_id: U64, reason: String) {
+        let buyer_id = env::predecessor_account_id();
+
+        //get the order
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            order.buyer_id == buyer_id,
+            "Only the buyer can request a return"
+        );
+        require!(
+            order.status == OrderStatus::Completed,
+            "Only completed orders can be returned"
+        );
+        require!(
+            order.return_request.is_none(),
+            "A return was already requested"
+        );
+        require!(!reason.is_empty(), "Reason can't be empty");
+
+        //returns are refunded from the reserve, so only while it is held
+        let reserve_until = order.reserve_until.expect("Return window has passed");
+        require!(
+            env::block_timestamp_ms() < reserve_until,
+            "Return window has passed"
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        let now = env::block_timestamp_ms();
+        order.return_request = Some(OrderReturn {
+            reason: reason.clone(),
+            status: ReturnStatus::Requested,
+            requested_at: now,
+            respond_by: now + RETURN_RESPONSE_TIME,
+            shipment: None,
+        });
+        self.orders_by_id.insert(&order_id.into(), &order);
+
+        //the return is kept with the order, the buyer pays for its bytes
+        let storage_bytes = env::storage_usage() - initial_storage_usage;
+        self.internal_storage_charge(&buyer_id, storage_bytes);
+        order.storage_bytes += storage_bytes;
+        self.orders_by_id.insert(&order_id.into(), &order);
+
+        // Emit NearEvent
+        NearEvent::return_request(ReturnRequestData::new(order_id, buyer_id, reason)).emit();
+    }
+
+    fn return_accept(&mut self, order_id: U64, shipment: ShippingDetails) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can accept a return"
+        );
+
+        //get the order
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        let mut request = order
+            .return_request
+            .take()
+            .expect("No return was requested");
+        require!(
+            order.status == OrderStatus::Completed && request.status == ReturnStatus::Requested,
+            "Return is not waiting for the seller"
+        );
+
+        //the shipment details are the seller's, their bytes are charged to the owner
+        let initial_storage_usage = env::storage_usage();
+        request.status = ReturnStatus::Accepted;
+        request.shipment = Some(shipment.clone());
+        order.return_request = Some(request);
+        order.status = OrderStatus::Returned;
+        order.reserve_until = None;
+        self.orders_by_id.insert(&order_id.into(), &order);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        //the return is closed, the buyer no longer pays for the order
+        self.internal_storage_release(&order.buyer_id, order.storage_bytes);
+
+        // Emit NearEvent
+        NearEvent::return_accept(ReturnAcceptData::new(
+            order_id,
+            U128(order.amount),
+            shipment,
+        ))
+        .emit();
+
+        //the reserve goes back to the buyer, the seller restocks once the item is back
+        self.internal_payout(order.buyer_id, order.amount, &order.ft_token_id);
+    }
+
+    fn return_decline(&mut self, order_id: U64) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can decline a return"
+        );
+
+        //get the order
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        let request = order
+            .return_request
+            .as_mut()
+            .expect("No return was requested");
+        require!(
+            order.status == OrderStatus::Completed && request.status == ReturnStatus::Requested,
+            "Return is not waiting for the seller"
+        );
+
+        //the buyer can escalate it with start_dispute while the reserve is held,
+        //a decline close to the end of the window still leaves the buyer time for it
+        request.status = ReturnStatus::Declined;
+        let dispute_until = env::block_timestamp_ms() + RETURN_DISPUTE_TIME;
+        if order.reserve_until.unwrap() < dispute_until {
+            order.reserve_until = Some(dispute_until);
+        }
+        self.orders_by_id.insert(&order_id.into(), &order);
+
+        // Emit NearEvent
+        NearEvent::return_decline(ReturnDeclineData::new(order_id)).emit();
+    }
+
+    fn return_reserve_release(&mut self, order_id: U64) {
+        //get the order
+        let mut order = self
+            .orders_by_id
+            .get(&order_id.into())
+            .expect("Order does not exist");
+        require!(
+            order.status == OrderStatus::Completed,
+            "Order is not completed"
+        );
+
+        //anyone can release the reserve to the seller once the return window is over
+        let reserve_until = order.reserve_until.expect("Reserve was already released");
+        require!(
+            env::block_timestamp_ms() >= reserve_until,
+            "Return window has not passed yet"
+        );
+
+        //a return waiting for the seller keeps the reserve held, the buyer disputes it once overdue
+        if let Some(request) = &order.return_request {
+            require!(
+                request.status != ReturnStatus::Requested,
+                "Return is waiting for the seller"
+            );
+        }
+
+        order.reserve_until = None;
+        self.orders_by_id.insert(&order_id.into(), &order);
+
+        //the order is kept for history but no longer charged to the buyer
+        self.internal_storage_release(&order.buyer_id, order.storage_bytes);
+
+        // Emit NearEvent
+        NearEvent::return_reserve_release(ReturnReserveReleaseData::new(order_id)).emit();
+
+        self.internal_payout_seller(order_id.into(), order.amount, &order.ft_token_id);
+    }
+}
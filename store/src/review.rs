@@ -37,6 +37,7 @@ pub trait ReviewManager {
 
 #[near_bindgen]
 impl ReviewManager for Contract {
+    #[payable]
     fn item_review(&mut self, item_id: U64, rating: u8, comment: Option<String>) -> U64 {
         // Check if item exists
         require!(
@@ -63,6 +64,12 @@ impl ReviewManager for Contract {
 
         require!(rating <= 5, "Rating must be between 0 and 5");
 
+        //the attached deposit goes to the reviewer's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&env::predecessor_account_id(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
         let review_id = self.reviews_by_id.len();
         let review = Review {
             reviewer_id: env::predecessor_account_id(),
@@ -103,6 +110,8 @@ impl ReviewManager for Contract {
         self.reviews_by_item_id
             .insert(&item_id.into(), &item_reviews_ids);
 
+        self.internal_storage_update(&env::predecessor_account_id(), initial_storage_usage);
+
         // emit NearEvent
         NearEvent::review_create(ReviewCreateData::new(
             item_id,
@@ -0,0 +1,140 @@
+/**
+ *  Staff
+ *
+ * Methods:
+ *
+ * - get_staff_role
+ *
+ * - staff_grant
+ * - staff_revoke
+ *
+ */
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum StaffRole {
+    // every permission below, auctions, coupons, offers, returns, staff and funds stay owner-only
+    Manager,
+    // ships and cancels orders
+    Fulfilment,
+    // creates and updates items and collections
+    CatalogEditor,
+}
+
+#[derive(PartialEq)]
+pub enum Permission {
+    // item_create, item_update, item_delete, item_restock, item_set_stock, item_set_status,
+    // item_set_milestones and the collection methods
+    Catalog,
+    // order_shipped
+    Shipping,
+    // order_cancel, order_refund
+    Cancel,
+    // update_store_metadata, set_handling_time, store_pause and store_resume
+    Metadata,
+}
+
+impl StaffRole {
+    pub fn can(&self, permission: &Permission) -> bool {
+        match self {
+            StaffRole::Manager => true,
+            StaffRole::Fulfilment => {
+                *permission == Permission::Shipping || *permission == Permission::Cancel
+            }
+            StaffRole::CatalogEditor => *permission == Permission::Catalog,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonStaff {
+    pub account_id: AccountId,
+    pub role: StaffRole,
+}
+
+pub trait StaffProvider {
+    fn get_staff_role(&self, account_id: AccountId) -> Option<StaffRole>;
+}
+
+#[near_bindgen]
+impl StaffProvider for Contract {
+    fn get_staff_role(&self, account_id: AccountId) -> Option<StaffRole> {
+        self.staff.get(&account_id)
+    }
+}
+
+pub trait StaffManager {
+    fn staff_grant(&mut self, account_id: AccountId, role: StaffRole);
+    fn staff_revoke(&mut self, account_id: AccountId);
+}
+
+#[near_bindgen]
+impl StaffManager for Contract {
+    #[payable]
+    fn staff_grant(&mut self, account_id: AccountId, role: StaffRole) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can grant a role"
+        );
+        require!(account_id != self.owner_id, "The owner can't be staff");
+
+        //the attached deposit goes to the owner's storage balance
+        if env::attached_deposit() > 0 {
+            self.internal_storage_deposit(&self.owner_id.clone(), env::attached_deposit());
+        }
+        let initial_storage_usage = env::storage_usage();
+
+        //granting a new role replaces the previous one
+        self.staff.insert(&account_id, &role);
+
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::staff_grant(StaffGrantData::new(account_id, role)).emit();
+    }
+
+    fn staff_revoke(&mut self, account_id: AccountId) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only owner can revoke a role"
+        );
+        require!(
+            self.staff.get(&account_id).is_some(),
+            "Account is not staff"
+        );
+
+        let initial_storage_usage = env::storage_usage();
+        self.staff.remove(&account_id);
+        self.internal_storage_update(&self.owner_id.clone(), initial_storage_usage);
+
+        // Emit NearEvent
+        NearEvent::staff_revoke(StaffRevokeData::new(account_id)).emit();
+    }
+}
+
+impl Contract {
+    /// Whether the account is the owner or staff with the permission.
+    pub(crate) fn internal_has_permission(
+        &self,
+        account_id: &AccountId,
+        permission: Permission,
+    ) -> bool {
+        *account_id == self.owner_id
+            || self
+                .staff
+                .get(account_id)
+                .is_some_and(|role| role.can(&permission))
+    }
+
+    /// Checks that the caller is the owner or staff with the permission, panics with `message` otherwise.
+    pub(crate) fn internal_assert_permission(&self, permission: Permission, message: &str) {
+        require!(
+            self.internal_has_permission(&env::predecessor_account_id(), permission),
+            message
+        );
+    }
+}
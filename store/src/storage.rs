@@ -0,0 +1,246 @@
+/**
+ *  Storage Management (NEP-145)
+ *
+ * Methods:
+ *
+ * - storage_balance_of
+ * - storage_balance_bounds
+ *
+ * - storage_deposit
+ * - storage_withdraw
+ * - storage_unregister
+ *
+ */
+use crate::*;
+
+// Storage account, bytes are charged against the deposit as they are used
+#[derive(BorshDeserialize, BorshSerialize, Default)]
+pub struct StorageAccount {
+    pub deposit: Balance,
+    pub used_bytes: StorageUsage,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+pub trait StorageManagementProvider {
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance>;
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds;
+}
+
+#[near_bindgen]
+impl StorageManagementProvider for Contract {
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts
+            .get(&account_id)
+            .map(|account| self.internal_storage_balance(&account))
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(self.internal_storage_min()),
+            max: None,
+        }
+    }
+}
+
+pub trait StorageManagement {
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance;
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance;
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool;
+}
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let mut amount = env::attached_deposit();
+
+        // registration only keeps the minimum and refunds the rest
+        if registration_only.unwrap_or(false) {
+            if self.storage_accounts.contains_key(&account_id) {
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+                amount = 0;
+            } else {
+                let min = self.internal_storage_min();
+                require!(amount >= min, "Not enough deposit to register the account");
+                if amount > min {
+                    Promise::new(env::predecessor_account_id()).transfer(amount - min);
+                }
+                amount = min;
+            }
+        }
+
+        self.internal_storage_deposit(&account_id, amount);
+
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        require!(
+            env::attached_deposit() == ONE_YOCTO,
+            "Requires attached deposit of exactly 1 yoctoNEAR"
+        );
+
+        let account_id = env::predecessor_account_id();
+        let mut account = self
+            .storage_accounts
+            .get(&account_id)
+            .expect("Account is not registered");
+
+        let available = self.internal_storage_balance(&account).available.0;
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        require!(
+            amount <= available,
+            "Can't withdraw more than the available storage balance"
+        );
+
+        account.deposit -= amount;
+        self.storage_accounts.insert(&account_id, &account);
+
+        if amount > 0 {
+            Promise::new(account_id).transfer(amount);
+        }
+
+        self.internal_storage_balance(&account)
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        require!(
+            env::attached_deposit() == ONE_YOCTO,
+            "Requires attached deposit of exactly 1 yoctoNEAR"
+        );
+
+        let account_id = env::predecessor_account_id();
+        let account = match self.storage_accounts.get(&account_id) {
+            Some(account) => account,
+            None => return false,
+        };
+
+        //open orders and offers hold funds and reviews are kept, none of them can be dropped
+        require!(
+            force != Some(true),
+            "Force unregistering is not supported, close the orders and offers first"
+        );
+        require!(
+            account.used_bytes == 0,
+            "Can't unregister while the account pays for stored orders, offers or reviews"
+        );
+
+        self.storage_accounts.remove(&account_id);
+        if account.deposit > 0 {
+            Promise::new(account_id).transfer(account.deposit);
+        }
+        true
+    }
+}
+
+impl Contract {
+    /// Measures the bytes taken by a storage account entry, which is the registration minimum.
+    pub(crate) fn internal_measure_account_storage_usage(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let tmp_account_id: AccountId = "a".repeat(64).parse().unwrap();
+        self.storage_accounts
+            .insert(&tmp_account_id, &StorageAccount::default());
+        self.account_storage_usage = env::storage_usage() - initial_storage_usage;
+        self.storage_accounts.remove(&tmp_account_id);
+    }
+
+    pub(crate) fn internal_storage_min(&self) -> Balance {
+        self.account_storage_usage as Balance * env::storage_byte_cost()
+    }
+
+    pub(crate) fn internal_storage_balance(&self, account: &StorageAccount) -> StorageBalance {
+        let locked =
+            self.internal_storage_min() + account.used_bytes as Balance * env::storage_byte_cost();
+        StorageBalance {
+            total: U128(account.deposit),
+            available: U128(account.deposit.saturating_sub(locked)),
+        }
+    }
+
+    /// Adds `amount` to the storage deposit of `account_id`, registering it if needed.
+    pub(crate) fn internal_storage_deposit(&mut self, account_id: &AccountId, amount: Balance) {
+        let mut account = self.storage_accounts.get(account_id).unwrap_or_else(|| {
+            require!(
+                amount >= self.internal_storage_min(),
+                "Not enough deposit to register the account"
+            );
+            StorageAccount::default()
+        });
+        account.deposit += amount;
+        self.storage_accounts.insert(account_id, &account);
+    }
+
+    /// Charges or releases the bytes used by `account_id` since `initial_storage_usage`.
+    pub(crate) fn internal_storage_update(
+        &mut self,
+        account_id: &AccountId,
+        initial_storage_usage: StorageUsage,
+    ) {
+        let storage_usage = env::storage_usage();
+        if storage_usage > initial_storage_usage {
+            self.internal_storage_charge(account_id, storage_usage - initial_storage_usage);
+        } else {
+            self.internal_storage_release(account_id, initial_storage_usage - storage_usage);
+        }
+    }
+
+    pub(crate) fn internal_storage_charge(&mut self, account_id: &AccountId, bytes: StorageUsage) {
+        let mut account = self
+            .storage_accounts
+            .get(account_id)
+            .expect("Account is not registered, call storage_deposit first");
+        account.used_bytes += bytes;
+        require!(
+            account.deposit
+                >= self.internal_storage_min()
+                    + account.used_bytes as Balance * env::storage_byte_cost(),
+            "Not enough storage balance, call storage_deposit first"
+        );
+        self.storage_accounts.insert(account_id, &account);
+    }
+
+    /// Charges the bytes even if the deposit doesn't cover them, for records that can't be refused.
+    /// The account has no available balance until it deposits the difference.
+    pub(crate) fn internal_storage_charge_unchecked(
+        &mut self,
+        account_id: &AccountId,
+        bytes: StorageUsage,
+    ) {
+        let mut account = self.storage_accounts.get(account_id).unwrap_or_default();
+        account.used_bytes += bytes;
+        self.storage_accounts.insert(account_id, &account);
+    }
+
+    pub(crate) fn internal_storage_release(&mut self, account_id: &AccountId, bytes: StorageUsage) {
+        if let Some(mut account) = self.storage_accounts.get(account_id) {
+            account.used_bytes = account.used_bytes.saturating_sub(bytes);
+            self.storage_accounts.insert(account_id, &account);
+        }
+    }
+}
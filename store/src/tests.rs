@@ -1,6 +1,6 @@
 #[cfg(test)]
 use crate::*;
-use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
 use near_sdk::testing_env;
 use near_sdk::AccountId;
 
@@ -23,12 +23,25 @@ fn sample_store_metadata() -> StoreMetadata {
         email: Some("a@example.com".to_string()),
         phone: Some("1234567890".to_string()),
         terms: Some("Short version of the store terms".to_string()),
+        public_key: None,
         tags: Some(vec!["store".to_string(), "awesome".to_string()]),
         created_at: Some(1234567890.to_string()),
         updated_at: Some(1234567890.to_string()),
     }
 }
 
+fn sample_arbiter() -> AccountId {
+    "arbiter.testnet".parse().unwrap()
+}
+
+fn sample_shipping() -> ShippingDetails {
+    ShippingDetails {
+        carrier: "DHL".to_string(),
+        tracking_number: "JD0002".to_string(),
+        expected_delivery: None,
+    }
+}
+
 fn sample_item_metadata() -> ItemMetadata {
     ItemMetadata {
         title: "My Item".to_string(),
@@ -43,25 +56,42 @@ fn sample_item_metadata() -> ItemMetadata {
 
 #[test]
 fn test_store_metadata() {
-    let context = get_context(accounts(0));
+    let mut context = get_context(accounts(0));
     testing_env!(context.build());
-    let mut contract = Contract::new(accounts(0), sample_store_metadata());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
 
-    assert_eq!(contract.store_metadata().name, sample_store_metadata().name);
+    assert_eq!(
+        contract.store_metadata().metadata.name,
+        sample_store_metadata().name
+    );
     let new_metadata = StoreMetadata {
         name: "New Test Store".to_string(),
         ..sample_store_metadata()
     };
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
     contract.update_store_metadata(new_metadata.clone());
-    assert_eq!(contract.store_metadata().name, new_metadata.name,);
+    assert_eq!(contract.store_metadata().metadata.name, new_metadata.name,);
 }
 
 #[test]
 fn test_item_management() {
-    let context = get_context(accounts(0));
-    testing_env!(context.build());
-    let mut contract = Contract::new(accounts(0), sample_store_metadata());
-    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata());
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
     assert_eq!(
         contract
             .get_item(item_id)
@@ -84,7 +114,13 @@ fn test_item_management() {
         ]),
         tags: Some(vec!["awesome".to_string(), "cool".to_string()]),
     };
-    contract.item_update(item_id, U128(ONE_NEAR * 2), new_metadata.clone());
+    contract.item_update(
+        item_id,
+        U128(ONE_NEAR * 2),
+        new_metadata.clone(),
+        None,
+        None,
+    );
     assert_eq!(
         contract
             .get_item(item_id)
@@ -105,15 +141,22 @@ fn test_item_management() {
 #[test]
 fn test_order_management() {
     let mut context = get_context(accounts(0));
-    testing_env!(context.build());
-    let mut contract = Contract::new(accounts(0), sample_store_metadata());
-    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata());
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
 
     testing_env!(context
-        .attached_deposit(ONE_NEAR)
+        .attached_deposit(ONE_NEAR * 2)
         .predecessor_account_id(accounts(2))
         .build());
-    let order_id = contract.item_buy(item_id);
+    let order_id = contract.item_buy(item_id, None, None, None, None);
 
     assert_eq!(contract.get_order(order_id).unwrap().buyer_id, accounts(2));
     assert_eq!(
@@ -130,6 +173,14 @@ fn test_order_management() {
             .unwrap(),
         OrderStatus::Pending.try_to_vec().unwrap()
     );
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
     contract.order_complete(order_id);
 
     assert_eq!(
@@ -142,9 +193,13 @@ fn test_order_management() {
         OrderStatus::Completed.try_to_vec().unwrap()
     );
 
-    let order_id = contract.item_buy(item_id);
+    testing_env!(context.attached_deposit(ONE_NEAR * 2).build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
 
-    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
 
     contract.order_cancel(order_id);
     assert_eq!(
@@ -159,116 +214,2803 @@ fn test_order_management() {
 }
 
 #[test]
-fn test_dispute_management() {
+fn test_ft_payment() {
     let mut context = get_context(accounts(0));
-    testing_env!(context.build());
-    let mut contract = Contract::new(accounts(0), sample_store_metadata());
-    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata());
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.ft_token_add(accounts(3));
+    let item_id = contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        Some(HashMap::from([(accounts(3), U128(100))])),
+        10,
+        None,
+        None,
+    );
+    assert_eq!(
+        contract.get_item(item_id).unwrap().ft_prices[&accounts(3)],
+        U128(100)
+    );
 
-    testing_env!(context
-        .attached_deposit(ONE_NEAR)
-        .predecessor_account_id(accounts(2))
-        .build());
-    let order_id = contract.item_buy(item_id);
+    // the buyer pays for the order's storage up front
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.storage_deposit(None, None);
 
+    // the token contract forwards the buyer's transfer
     testing_env!(context
-        .predecessor_account_id(accounts(0))
-        .attached_deposit(ONE_NEAR)
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(3))
         .build());
+    let unused =
+        contract.ft_on_transfer(accounts(2), U128(150), "{\"item_id\": \"0\"}".to_string());
+    match unused {
+        PromiseOrValue::Value(unused) => assert_eq!(unused.0, 50),
+        PromiseOrValue::Promise(_) => panic!("Expected the unused amount"),
+    }
+
+    // the events are the buyer's, not the token contract's
+    let actor = format!("\"actor_id\":\"{}\"", accounts(2));
+    assert!(get_logs().iter().all(|log| log.contains(&actor)));
 
-    // TODO: Fix this test
-    // contract.start_dispute(order_id);
+    let order = contract.get_order(U64(0)).unwrap();
+    assert_eq!(order.buyer_id, accounts(2));
+    assert_eq!(order.item_id, u64::from(item_id));
+    assert_eq!(order.amount, 100);
+    assert_eq!(order.ft_token_id, Some(accounts(3)));
 
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.order_shipped(U64(0), sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(U64(0));
     assert_eq!(
         contract
-            .get_order(order_id)
+            .get_order(U64(0))
             .unwrap()
             .status
             .try_to_vec()
             .unwrap(),
-        OrderStatus::Disputed.try_to_vec().unwrap()
+        OrderStatus::Completed.try_to_vec().unwrap()
     );
+}
 
-    testing_env!(context.predecessor_account_id(accounts(1)).build());
-    contract.dispute_resolve(order_id, DisputeResolution::BuyerWon);
+#[test]
+#[should_panic(expected = "Item is not priced in this token")]
+fn test_ft_payment_wrong_token() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.ft_token_add(accounts(3));
+    contract.ft_token_add(accounts(4));
+    contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        Some(HashMap::from([(accounts(3), U128(100))])),
+        10,
+        None,
+        None,
+    );
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(4))
+        .build());
+    contract.ft_on_transfer(accounts(2), U128(100), "{\"item_id\": \"0\"}".to_string());
+}
+
+#[test]
+fn test_order_commission() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        250,
+    );
+    assert_eq!(contract.get_store_treasury(), accounts(5));
+    assert_eq!(contract.get_store_commission_bps(), 250);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
 
+    // 2.5% of 1 NEAR goes to the treasury
+    assert!(get_logs()
+        .iter()
+        .any(|log| log.contains("\"event\":\"order_commission\"")
+            && log.contains(&format!("\"amount\":\"{}\"", ONE_NEAR / 40))
+            && log.contains(&format!("\"treasury_id\":\"{}\"", accounts(5)))));
+    assert_eq!(contract.get_claimable(accounts(5)).near.0, ONE_NEAR / 40);
     assert_eq!(
-        contract
-            .get_order(order_id)
-            .unwrap()
-            .status
-            .try_to_vec()
-            .unwrap(),
-        OrderStatus::Resolved.try_to_vec().unwrap()
+        contract.get_claimable(accounts(0)).near.0,
+        ONE_NEAR - ONE_NEAR / 40
     );
 }
 
 #[test]
-fn test_review_management() {
+fn test_dispute_draw_commission() {
     let mut context = get_context(accounts(0));
-    testing_env!(context.build());
-    let mut contract = Contract::new(accounts(0), sample_store_metadata());
-    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata());
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        250,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
 
     testing_env!(context
-        .attached_deposit(ONE_NEAR)
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.start_dispute(order_id, "Item arrived damaged".to_string());
+    testing_env!(
+        context.predecessor_account_id(accounts(0)).build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"7".to_vec())],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Ok("7".to_string()));
+
+    // the seller's half pays the marketplace commission like any other payout
+    testing_env!(context.predecessor_account_id(sample_arbiter()).build());
+    contract.dispute_resolve(order_id, DisputeResolution::Draw);
+    let half = ONE_NEAR * 4 / 10;
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, half);
+    assert_eq!(contract.get_claimable(accounts(5)).near.0, half / 40);
+    assert_eq!(contract.get_claimable(accounts(0)).near.0, half - half / 40);
+}
+
+#[test]
+fn test_withdraw() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    assert_eq!(contract.get_escrow(None).0, ONE_NEAR);
+
+    // cancelling credits the buyer instead of transferring
+    testing_env!(context.attached_deposit(0).build());
+    contract.order_cancel(order_id);
+    assert_eq!(contract.get_escrow(None).0, 0);
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, ONE_NEAR);
+
+    testing_env!(context.attached_deposit(ONE_YOCTO).build());
+    contract.withdraw(None, None);
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, 0);
+    assert!(contract.claimable.is_empty());
+
+    // a failed transfer makes the funds claimable again
+    testing_env!(
+        context
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(0))
+            .current_account_id(accounts(0))
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Failed],
+    );
+    contract.withdraw_callback(accounts(2), U128(ONE_NEAR), None);
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, ONE_NEAR);
+    assert_eq!(contract.claimable.near, ONE_NEAR);
+    assert_eq!(contract.get_escrow(None).0, 0);
+}
+
+#[test]
+#[should_panic(expected = "Can't delete store with balances left to withdraw")]
+fn test_delete_self_with_balances() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
         .predecessor_account_id(accounts(2))
         .build());
-    let order_id = contract.item_buy(item_id);
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
 
+    // the seller's payout is still waiting to be withdrawn
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
     contract.order_complete(order_id);
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.delete_self();
+}
+
+#[test]
+fn test_storage_management() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    // the owner's deposit covers the item
+    let owner_balance = contract.storage_balance_of(accounts(0)).unwrap();
+    assert_eq!(owner_balance.total.0, ONE_NEAR);
+    assert!(owner_balance.available.0 < ONE_NEAR);
+
+    // whatever is attached above the price goes to the buyer's storage balance
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    let buyer_balance = contract.storage_balance_of(accounts(2)).unwrap();
+    assert_eq!(buyer_balance.total.0, ONE_NEAR);
+    assert!(buyer_balance.available.0 < ONE_NEAR);
 
+    // cancelling the order releases its storage
+    testing_env!(context.attached_deposit(0).build());
+    contract.order_cancel(order_id);
+    let buyer_balance = contract.storage_balance_of(accounts(2)).unwrap();
     assert_eq!(
-        contract
-            .get_order(order_id)
-            .unwrap()
-            .status
-            .try_to_vec()
-            .unwrap(),
-        OrderStatus::Completed.try_to_vec().unwrap()
+        buyer_balance.available.0,
+        ONE_NEAR - contract.storage_balance_bounds().min.0
     );
 
-    let review_id = contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
+    testing_env!(context.attached_deposit(ONE_YOCTO).build());
+    let buyer_balance = contract.storage_withdraw(None);
+    assert_eq!(buyer_balance.available.0, 0);
+    assert_eq!(
+        buyer_balance.total.0,
+        contract.storage_balance_bounds().min.0
+    );
 
-    let review = contract.get_review(review_id).unwrap();
+    // the buyer's orders are finished, the account can unregister
+    assert!(contract.storage_unregister(None));
+    assert!(contract.storage_balance_of(accounts(2)).is_none());
+    assert!(!contract.storage_unregister(None));
+}
 
-    assert_eq!(review.reviewer_id, accounts(2));
-    assert_eq!(review.rating, 5);
-    assert_eq!(review.comment, "Awesome".to_string());
+#[test]
+#[should_panic(expected = "Can't unregister while the account pays for stored orders")]
+fn test_storage_unregister_with_orders() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, None, None);
+
+    // the pending order is still charged to the buyer
+    testing_env!(context.attached_deposit(ONE_YOCTO).build());
+    contract.storage_unregister(None);
 }
 
 #[test]
-fn test_enumeration() {
+#[should_panic(expected = "Force unregistering is not supported")]
+fn test_storage_unregister_force() {
     let mut context = get_context(accounts(0));
-    testing_env!(context.build());
-    let mut contract = Contract::new(accounts(0), sample_store_metadata());
-    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata());
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
 
     testing_env!(context
-        .attached_deposit(ONE_NEAR)
+        .attached_deposit(contract.storage_balance_bounds().min.0)
         .predecessor_account_id(accounts(2))
         .build());
-    let order_id = contract.item_buy(item_id);
-    contract.order_complete(order_id);
-    contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
+    contract.storage_deposit(None, None);
+    testing_env!(context.attached_deposit(ONE_YOCTO).build());
+    contract.storage_unregister(Some(true));
+}
+
+#[test]
+fn test_item_stock() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 2, None, None);
+
+    // buying the whole stock deactivates the item
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 3)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, Some(2), None, None, None);
+    assert_eq!(contract.get_order(order_id).unwrap().quantity, 2);
+    assert_eq!(contract.get_order(order_id).unwrap().amount, ONE_NEAR * 2);
+    let item = contract.get_item(item_id).unwrap();
+    assert_eq!(item.stock, 0);
+    assert_eq!(item.status, ItemStatus::Inactive);
+
+    // cancelling returns the units and reactivates the item
+    testing_env!(context.attached_deposit(0).build());
+    contract.order_cancel(order_id);
+    let item = contract.get_item(item_id).unwrap();
+    assert_eq!(item.stock, 2);
+    assert_eq!(item.status, ItemStatus::Active);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.item_restock(item_id, 3, None);
+    assert_eq!(contract.get_item(item_id).unwrap().stock, 5);
+    contract.item_set_stock(item_id, 0, None);
+    assert_eq!(
+        contract.get_item(item_id).unwrap().status,
+        ItemStatus::Inactive
+    );
+}
 
+#[test]
+fn test_item_lifecycle() {
+    let mut context = get_context(accounts(0));
     testing_env!(context
         .attached_deposit(ONE_NEAR)
-        .predecessor_account_id(accounts(3))
+        .block_timestamp(1_000_000_000)
+        .build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        Some(ItemStatus::Draft),
+    );
+    assert_eq!(
+        contract.get_item(item_id).unwrap().status,
+        ItemStatus::Draft
+    );
+
+    // scheduled items are published once the date is reached
+    contract.item_set_status(item_id, ItemStatus::Scheduled(5_000));
+    assert_eq!(
+        contract.get_item(item_id).unwrap().status,
+        ItemStatus::Scheduled(5_000)
+    );
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(5_000_000_000)
+        .build());
+    assert_eq!(
+        contract.get_item(item_id).unwrap().status,
+        ItemStatus::Active
+    );
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    // paused items keep their stock
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
         .build());
+    contract.item_set_status(item_id, ItemStatus::Paused);
+    contract.item_restock(item_id, 1, None);
+    assert_eq!(
+        contract.get_item(item_id).unwrap().status,
+        ItemStatus::Paused
+    );
 
-    let order_id = contract.item_buy(item_id);
+    // items with orders are archived instead of deleted
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
     contract.order_complete(order_id);
-    let review_id = contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.item_delete(item_id);
+    assert_eq!(
+        contract.get_item(item_id).unwrap().status,
+        ItemStatus::Archived
+    );
+}
 
-    assert!(contract.get_review(review_id).is_some());
+#[test]
+#[should_panic(expected = "Item is not available")]
+fn test_item_draft_buy() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        Some(ItemStatus::Draft),
+    );
 
     testing_env!(context
-        .attached_deposit(ONE_NEAR)
-        .predecessor_account_id(accounts(4))
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
         .build());
+    contract.item_buy(item_id, None, None, None, None);
+}
+
+#[test]
+fn test_tags() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let guitar_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    assert_eq!(
+        contract
+            .get_items_by_tag("cool".to_string(), None, None)
+            .len(),
+        2
+    );
+
+    // updating the tags moves the item in the index
+    contract.item_update(
+        guitar_id,
+        U128(ONE_NEAR),
+        ItemMetadata {
+            tags: Some(vec!["cool".to_string(), "music".to_string()]),
+            ..sample_item_metadata()
+        },
+        None,
+        None,
+    );
+    let tags = contract.get_tags(None, None);
+    assert_eq!(tags.len(), 3);
+    assert_eq!(
+        tags.iter().find(|tag| tag.tag == "awesome").unwrap().items,
+        1
+    );
+    let items = contract.get_items_by_tag("music".to_string(), None, None);
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, guitar_id);
+
+    // tags without items are no longer listed
+    contract.item_delete(guitar_id);
+    assert!(contract
+        .get_items_by_tag("music".to_string(), None, None)
+        .is_empty());
+    assert_eq!(contract.get_tags(None, None).len(), 2);
+
+    // ids aren't reused, a new item doesn't take the place of the one left
+    let drum_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    assert_eq!(drum_id, U64(2));
+    assert_eq!(
+        contract
+            .get_items_by_tag("awesome".to_string(), None, None)
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn test_collections() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let guitar_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    let drum_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    let summer_id = contract.collection_create(CollectionMetadata {
+        name: "Summer".to_string(),
+        description: None,
+        cover: None,
+        position: 2,
+    });
+    let music_id = contract.collection_create(CollectionMetadata {
+        name: "Music".to_string(),
+        description: Some("Instruments".to_string()),
+        cover: None,
+        position: 1,
+    });
+
+    // collections are listed by position
+    let collections = contract.get_collections(None, None);
+    assert_eq!(collections[0].id, music_id);
+    assert_eq!(collections[1].id, summer_id);
+
+    // an item can be in several collections
+    contract.collection_add_item(music_id, guitar_id);
+    contract.collection_add_item(music_id, drum_id);
+    contract.collection_add_item(summer_id, guitar_id);
+    assert_eq!(
+        contract.get_item(guitar_id).unwrap().collections,
+        vec![music_id, summer_id]
+    );
+    assert_eq!(contract.get_collection(music_id).unwrap().items, 2);
+    let items = contract.get_items_for_collection(music_id, Some(U64(1)), Some(U64(1)));
+    assert_eq!(items.len(), 1);
+
+    contract.collection_remove_item(music_id, drum_id);
+    assert_eq!(
+        contract
+            .get_items_for_collection(music_id, None, None)
+            .len(),
+        1
+    );
+
+    // a new position moves the collection in the listing
+    contract.collection_update(
+        music_id,
+        CollectionMetadata {
+            name: "Music".to_string(),
+            description: None,
+            cover: None,
+            position: 3,
+        },
+    );
+    let collections = contract.get_collections(None, None);
+    assert_eq!(collections[0].id, summer_id);
+    assert_eq!(collections[1].id, music_id);
+
+    // deleting a collection keeps its items
+    contract.collection_delete(summer_id);
+    assert!(contract.get_collection(summer_id).is_none());
+    assert_eq!(contract.get_collections(None, None).len(), 1);
+    assert_eq!(
+        contract.get_item(guitar_id).unwrap().collections,
+        vec![music_id]
+    );
+
+    // deleted items leave their collections
+    contract.item_delete(guitar_id);
+    assert!(contract
+        .get_items_for_collection(music_id, None, None)
+        .is_empty());
+}
 
-    let order_id = contract.item_buy(item_id);
+#[test]
+fn test_item_variants() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let variant = |price: u128, stock: u64| JsonItemVariant {
+        title: "Size".to_string(),
+        price: U128(price),
+        ft_prices: HashMap::new(),
+        stock,
+        images: None,
+    };
+    let item_id = contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        None,
+        0,
+        Some(HashMap::from([
+            ("s".to_string(), variant(ONE_NEAR, 1)),
+            ("xl".to_string(), variant(ONE_NEAR * 2, 3)),
+        ])),
+        None,
+    );
+    // the item's stock is the sum of its variants
+    assert_eq!(contract.get_item(item_id).unwrap().stock, 4);
+
+    // the variant's price and stock apply
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 3)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, Some("xl".to_string()), None, None);
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.amount, ONE_NEAR * 2);
+    assert_eq!(order.variant, Some("xl".to_string()));
+    let item = contract.get_item(item_id).unwrap();
+    assert_eq!(item.variants["xl"].stock, 2);
+    assert_eq!(item.stock, 3);
+
+    // cancelling returns the unit to the variant
+    testing_env!(context.attached_deposit(0).build());
+    contract.order_cancel(order_id);
+    assert_eq!(contract.get_item(item_id).unwrap().variants["xl"].stock, 3);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.item_set_stock(item_id, 0, Some("s".to_string()));
+    assert_eq!(contract.get_item(item_id).unwrap().stock, 3);
+
+    // dropping the variants drops their units, the item's own stock is set again
+    let item_id = contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        None,
+        0,
+        Some(HashMap::from([("s".to_string(), variant(ONE_NEAR, 2))])),
+        None,
+    );
+    contract.item_update(item_id, U128(ONE_NEAR), sample_item_metadata(), None, None);
+    let item = contract.get_item(item_id).unwrap();
+    assert_eq!(item.stock, 0);
+    assert_eq!(item.status, ItemStatus::Inactive);
+
+    contract.item_set_stock(item_id, 5, None);
+    let item = contract.get_item(item_id).unwrap();
+    assert_eq!(item.stock, 5);
+    assert_eq!(item.status, ItemStatus::Active);
+}
+
+#[test]
+#[should_panic(expected = "A variant must be chosen for this item")]
+fn test_item_variant_required() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR),
+        sample_item_metadata(),
+        None,
+        0,
+        Some(HashMap::from([(
+            "s".to_string(),
+            JsonItemVariant {
+                title: "Small".to_string(),
+                price: U128(ONE_NEAR),
+                ft_prices: HashMap::new(),
+                stock: 1,
+                images: None,
+            },
+        )])),
+        None,
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, None, None);
+}
+
+#[test]
+fn test_cart_checkout() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let first_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    let second_id = contract.item_create(
+        U128(ONE_NEAR * 2),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        None,
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 6)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_ids = contract.cart_checkout(
+        vec![
+            CartItem {
+                item_id: first_id,
+                quantity: Some(2),
+                variant: None,
+                coupon: None,
+            },
+            CartItem {
+                item_id: second_id,
+                quantity: None,
+                variant: None,
+                coupon: None,
+            },
+        ],
+        None,
+    );
+    assert_eq!(order_ids.len(), 2);
+
+    // the orders are linked and indexed like single purchases
+    let first = contract.get_order(order_ids[0]).unwrap();
+    let second = contract.get_order(order_ids[1]).unwrap();
+    assert_eq!(first.amount, ONE_NEAR * 2);
+    assert_eq!(second.amount, ONE_NEAR * 2);
+    assert_eq!(first.cart_id, Some(order_ids[0].into()));
+    assert_eq!(second.cart_id, Some(order_ids[0].into()));
+    assert_eq!(
+        contract
+            .get_orders_for_account(accounts(2), None, None)
+            .len(),
+        2
+    );
+    assert_eq!(contract.get_item(first_id).unwrap().stock, 8);
+
+    // the overpayment left after storage is refunded
+    let balance = contract.storage_balance_of(accounts(2)).unwrap();
+    assert_eq!(balance.available.0, 0);
+    assert!(balance.total.0 < ONE_NEAR * 2);
+}
+
+#[test]
+#[should_panic(expected = "Not enough stock for this order")]
+fn test_cart_checkout_stock() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 2, None, None);
+
+    // two lines of the same item over its stock
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 5)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let line = CartItem {
+        item_id,
+        quantity: Some(2),
+        variant: None,
+        coupon: None,
+    };
+    contract.cart_checkout(vec![line.clone(), line], None);
+}
+
+fn sample_coupon(code: &str, discount: Discount, scope: CouponScope) -> Coupon {
+    Coupon {
+        code: code.to_string(),
+        discount,
+        scope,
+        max_uses: None,
+        max_uses_per_account: Some(1),
+        starts_at: None,
+        expires_at: None,
+        uses: 0,
+    }
+}
+
+#[test]
+fn test_coupon() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR * 2),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        None,
+    );
+    contract.coupon_create(sample_coupon(
+        "TEN",
+        Discount::Percent(10),
+        CouponScope::Store,
+    ));
+    contract.coupon_create(sample_coupon(
+        "HALF",
+        Discount::Fixed(U128(ONE_NEAR)),
+        CouponScope::Items(vec![item_id]),
+    ));
+    assert_eq!(contract.get_coupons(None, None).len(), 2);
+
+    // the order records the discounted amount and the coupon
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, Some("TEN".to_string()), None);
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.amount, ONE_NEAR * 2 - ONE_NEAR / 5);
+    assert_eq!(order.coupon, Some("TEN".to_string()));
+    assert_eq!(contract.get_coupon("TEN".to_string()).unwrap().uses, 1);
+
+    let order_id = contract.item_buy(item_id, None, None, Some("HALF".to_string()), None);
+    assert_eq!(contract.get_order(order_id).unwrap().amount, ONE_NEAR);
+}
+
+#[test]
+#[should_panic(expected = "You have already used this coupon")]
+fn test_coupon_per_account_cap() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.coupon_create(sample_coupon(
+        "TEN",
+        Discount::Percent(10),
+        CouponScope::Store,
+    ));
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, Some("TEN".to_string()), None);
+    contract.item_buy(item_id, None, None, Some("TEN".to_string()), None);
+}
+
+#[test]
+#[should_panic(expected = "Coupon does not apply to this item")]
+fn test_coupon_scope() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.coupon_create(sample_coupon(
+        "BOOKS",
+        Discount::Percent(10),
+        CouponScope::Tags(vec!["books".to_string()]),
+    ));
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, Some("BOOKS".to_string()), None);
+}
+
+#[test]
+#[should_panic(expected = "Percent discount must be between 1 and 99")]
+fn test_coupon_full_percent() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.coupon_create(sample_coupon(
+        "FREE",
+        Discount::Percent(100),
+        CouponScope::Store,
+    ));
+}
+
+#[test]
+#[should_panic(expected = "Coupon can't cover the whole price")]
+fn test_coupon_full_price() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.coupon_create(sample_coupon(
+        "FREE",
+        Discount::Fixed(U128(ONE_NEAR)),
+        CouponScope::Store,
+    ));
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, Some("FREE".to_string()), None);
+}
+
+#[test]
+fn test_auction() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 1, None, None);
+    contract.auction_start(
+        item_id,
+        U128(ONE_NEAR),
+        U128(ONE_NEAR / 10),
+        10_000,
+        Some(1_000),
+    );
+
+    // the first bid must reach the reserve price, the rest of the deposit covers storage
+    let owner_balance = contract.storage_balance_of(accounts(0)).unwrap();
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(1_000_000_000)
+        .build());
+    contract.auction_bid(item_id, U128(ONE_NEAR));
+
+    // the bidder pays for the bytes of the bid, not the owner
+    assert_eq!(
+        contract
+            .storage_balance_of(accounts(0))
+            .unwrap()
+            .available
+            .0,
+        owner_balance.available.0
+    );
+    assert!(
+        contract
+            .storage_balance_of(accounts(2))
+            .unwrap()
+            .available
+            .0
+            < ONE_NEAR
+    );
+
+    // a bid close to the end extends the auction
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 3)
+        .predecessor_account_id(accounts(3))
+        .block_timestamp(9_500_000_000)
+        .build());
+    contract.auction_bid(item_id, U128(ONE_NEAR * 2));
+    let auction = contract.get_item(item_id).unwrap().auction.unwrap();
+    assert_eq!(auction.bidder_id, Some(accounts(3)));
+    assert_eq!(auction.ends_at, 10_500);
+
+    // the winner gets a normal order
+    testing_env!(context
+        .attached_deposit(0)
+        .block_timestamp(10_500_000_000)
+        .build());
+    let order_id = contract.auction_settle(item_id).unwrap();
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.buyer_id, accounts(3));
+    assert_eq!(order.amount, ONE_NEAR * 2);
+    let item = contract.get_item(item_id).unwrap();
+    assert!(item.auction.is_none());
+    assert_eq!(item.stock, 0);
+
+    // the outbid bidder got the bid back and no longer pays for it
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, ONE_NEAR);
+    assert_eq!(
+        contract
+            .storage_balance_of(accounts(2))
+            .unwrap()
+            .available
+            .0,
+        ONE_NEAR - contract.storage_balance_bounds().min.0
+    );
+}
+
+#[test]
+fn test_auction_settle_without_stock() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 1, None, None);
+    contract.auction_start(item_id, U128(ONE_NEAR), U128(ONE_NEAR / 10), 10_000, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(1_000_000_000)
+        .build());
+    contract.auction_bid(item_id, U128(ONE_NEAR));
+
+    // the owner takes the unit out of stock while the bid is held
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.item_set_stock(item_id, 0, None);
+
+    // settling still closes the auction and credits the bid back to the winner
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .block_timestamp(10_000_000_000)
+        .build());
+    assert_eq!(contract.auction_settle(item_id), None);
+    assert!(contract.get_item(item_id).unwrap().auction.is_none());
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, ONE_NEAR);
+    assert_eq!(contract.get_escrow(None).0, 0);
+}
+
+#[test]
+#[should_panic(expected = "Minimum increment must be greater than 0")]
+fn test_auction_zero_increment() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 1, None, None);
+    contract.auction_start(item_id, U128(ONE_NEAR), U128(0), 10_000, None);
+}
+
+#[test]
+#[should_panic(expected = "Item is sold by auction")]
+fn test_auction_item_buy() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 1, None, None);
+    contract.auction_start(item_id, U128(ONE_NEAR), U128(ONE_NEAR / 10), 10_000, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, None, None);
+}
+
+#[test]
+fn test_offer() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR * 2),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        None,
+    );
+
+    // the offer is escrowed, the rest of the deposit covers storage
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let offer_id = contract.offer_make(item_id, U128(ONE_NEAR), None);
+    assert_eq!(contract.get_offer(offer_id).unwrap().amount, ONE_NEAR);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.offer_counter(offer_id, U128(ONE_NEAR + ONE_NEAR / 2));
+    assert_eq!(
+        contract.get_offer(offer_id).unwrap().status,
+        OfferStatus::Countered
+    );
+
+    // accepting the counter-offer creates a pending order at the agreed amount
+    testing_env!(context
+        .attached_deposit(ONE_NEAR / 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.offer_accept_counter(offer_id).unwrap();
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.amount, ONE_NEAR + ONE_NEAR / 2);
+    assert_eq!(
+        order.status.try_to_vec().unwrap(),
+        OrderStatus::Pending.try_to_vec().unwrap()
+    );
+    let offer = contract.get_offer(offer_id).unwrap();
+    assert_eq!(offer.status, OfferStatus::Accepted);
+    assert_eq!(offer.order_id, Some(order_id.into()));
+}
+
+#[test]
+fn test_offer_reject_and_expire() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR * 2),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        None,
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let rejected_id = contract.offer_make(item_id, U128(ONE_NEAR), None);
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let expired_id = contract.offer_make(item_id, U128(ONE_NEAR), None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.offer_reject(rejected_id);
+    assert_eq!(
+        contract.get_offer(rejected_id).unwrap().status,
+        OfferStatus::Rejected
+    );
+
+    // accepting an offer that ran out of time refunds it instead
+    testing_env!(context
+        .block_timestamp((OFFER_DURATION + 1) * 1_000_000)
+        .build());
+    assert!(contract.offer_accept(expired_id).is_none());
+    assert_eq!(
+        contract.get_offer(expired_id).unwrap().status,
+        OfferStatus::Expired
+    );
+    assert_eq!(contract.get_orders(None, None).len(), 0);
+}
+
+#[test]
+fn test_offer_item_delete() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id = contract.item_create(
+        U128(ONE_NEAR * 2),
+        sample_item_metadata(),
+        None,
+        10,
+        None,
+        None,
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let offer_id = contract.offer_make(item_id, U128(ONE_NEAR), None);
+
+    // deleting the item rejects the offer and refunds the buyer
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.item_delete(item_id);
+    assert_eq!(
+        contract.get_offer(offer_id).unwrap().status,
+        OfferStatus::Rejected
+    );
+    assert_eq!(contract.get_escrow(None).0, 0);
+    assert_eq!(contract.get_claimable(accounts(2)).near.0, ONE_NEAR);
+}
+
+#[test]
+fn test_order_finalize() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_confirmation_window(1_000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .block_timestamp(5_000_000_000)
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.shipped_at, Some(5_000));
+    assert_eq!(order.finalize_after, Some(6_000));
+
+    // the seller finalizes once the buyer's window has passed
+    testing_env!(context.block_timestamp(6_000_000_000).build());
+    contract.order_finalize(order_id);
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Completed.try_to_vec().unwrap()
+    );
+}
+
+#[test]
+#[should_panic(expected = "Confirmation window has not passed yet")]
+fn test_order_finalize_too_early() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    // shortening the window doesn't apply to orders already shipped
+    contract.set_confirmation_window(1);
+    testing_env!(context.block_timestamp(1_000_000_000).build());
+    contract.order_finalize(order_id);
+}
+
+#[test]
+fn test_order_refund_late() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_handling_time(1_000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    assert_eq!(contract.get_order(order_id).unwrap().ship_by, 1_000);
+
+    // anyone can refund the order once the handling time has passed
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(3))
+        .block_timestamp(2_000_000_000)
+        .build());
+    contract.order_refund_late(order_id);
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Cancelled.try_to_vec().unwrap()
+    );
+    assert_eq!(contract.get_item(item_id).unwrap().stock, 10);
+    assert_eq!(contract.get_store_stats().late_shipments, 1);
+}
+
+#[test]
+#[should_panic(expected = "Handling time has not passed yet")]
+fn test_order_refund_late_too_early() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    contract.order_refund_late(order_id);
+}
+
+#[test]
+fn test_order_shipping() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, Some("encrypted".to_string()));
+    assert_eq!(
+        contract.get_order(order_id).unwrap().delivery_address,
+        Some("encrypted".to_string())
+    );
+
+    // the buyer can change the address until the order ships
+    testing_env!(context.attached_deposit(0).build());
+    contract.order_set_delivery_address(order_id, "encrypted again".to_string());
+    assert_eq!(
+        contract.get_order(order_id).unwrap().delivery_address,
+        Some("encrypted again".to_string())
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.order_shipped(order_id, sample_shipping());
+    let shipping = contract.get_order(order_id).unwrap().shipping.unwrap();
+    assert_eq!(shipping.carrier, "DHL");
+    assert_eq!(shipping.tracking_number, "JD0002");
+    assert!(get_logs()
+        .last()
+        .unwrap()
+        .contains("\"tracking_number\":\"JD0002\""));
+}
+
+#[test]
+fn test_order_refund() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    contract.order_refund(
+        order_id,
+        U128(ONE_NEAR / 4),
+        Some("Damaged box".to_string()),
+    );
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.amount, ONE_NEAR - ONE_NEAR / 4);
+    assert_eq!(order.refunds.len(), 1);
+    assert_eq!(order.refunds[0].amount, ONE_NEAR / 4);
+
+    // the rest of the escrow is paid on completion
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Completed.try_to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_return_accept() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_return_window(1000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    // the payout is held back while the buyer can return the item
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    assert_eq!(contract.get_claimable(accounts(0)).near.0, 0);
+    contract.return_request(order_id, "Wrong size".to_string());
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.return_accept(order_id, sample_shipping());
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(
+        order.status.try_to_vec().unwrap(),
+        OrderStatus::Returned.try_to_vec().unwrap()
+    );
+    assert!(order.reserve_until.is_none());
+    assert!(order.return_request.unwrap().shipment.is_some());
+}
+
+#[test]
+fn test_return_reserve_release() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_return_window(1000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    contract.return_request(order_id, "Changed my mind".to_string());
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.return_decline(order_id);
+
+    // the buyer keeps some time to dispute the decline past the return window
+    assert_eq!(
+        contract.get_order(order_id).unwrap().reserve_until,
+        Some(RETURN_DISPUTE_TIME)
+    );
+
+    // anyone can release the reserve once it is over
+    testing_env!(context
+        .predecessor_account_id(accounts(3))
+        .block_timestamp(RETURN_DISPUTE_TIME * 1_000_000)
+        .build());
+    contract.return_reserve_release(order_id);
+    assert!(contract
+        .get_order(order_id)
+        .unwrap()
+        .reserve_until
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "Return window has not passed yet")]
+fn test_return_reserve_release_too_early() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_return_window(1000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    contract.return_reserve_release(order_id);
+}
+
+#[test]
+fn test_return_dispute_overdue() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_return_window(1000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    contract.return_request(order_id, "Wrong size".to_string());
+
+    // the seller didn't answer in time, the buyer escalates the return
+    testing_env!(context
+        .block_timestamp(RETURN_RESPONSE_TIME * 1_000_000)
+        .build());
+    contract.start_dispute(order_id, "Return was not answered".to_string());
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Settling.try_to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_return_dispute_resolve() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context
+        .attached_deposit(ONE_NEAR)
+        .current_account_id("store.factory.testnet".parse().unwrap())
+        .build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_return_window(1000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    contract.return_request(order_id, "Wrong size".to_string());
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.return_decline(order_id);
+
+    // the buyer escalates the declined return and wins it
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.start_dispute(order_id, "Return was declined".to_string());
+    testing_env!(
+        context.build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"7".to_vec())],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Ok("7".to_string()));
+    testing_env!(context.predecessor_account_id(sample_arbiter()).build());
+    contract.dispute_resolve(order_id, DisputeResolution::BuyerWon);
+    let order = contract.get_order(order_id).unwrap();
+    assert!(order.reserve_until.is_none());
+    assert_eq!(
+        contract.get_claimable(accounts(2)).near.0,
+        ONE_NEAR * 8 / 10
+    );
+
+    // nothing is held for the order anymore, the store can be deleted
+    testing_env!(context
+        .attached_deposit(ONE_YOCTO)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.withdraw(None, None);
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.delete_self();
+}
+
+#[test]
+#[should_panic(
+    expected = "Only shipped orders, milestone orders or declined returns can be disputed"
+)]
+fn test_return_dispute_too_early() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.set_return_window(1000);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    contract.return_request(order_id, "Wrong size".to_string());
+    contract.start_dispute(order_id, "Return was not answered".to_string());
+}
+
+#[test]
+fn test_milestones() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.item_set_milestones(
+        item_id,
+        vec![
+            ItemMilestone {
+                description: "Sketch".to_string(),
+                share_bps: 3000,
+            },
+            ItemMilestone {
+                description: "Final artwork".to_string(),
+                share_bps: 7000,
+            },
+        ],
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.milestones.len(), 2);
+    assert_eq!(order.milestones[0].amount, ONE_NEAR * 3 / 10);
+
+    // each approval releases its share, the rest stays in escrow
+    testing_env!(context.attached_deposit(0).build());
+    contract.milestone_approve(order_id, 0);
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.amount, ONE_NEAR * 7 / 10);
+    assert_eq!(order.milestones[0].status, MilestoneStatus::Released);
+
+    // the last one completes the order
+    contract.milestone_approve(order_id, 1);
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Completed.try_to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_milestones_dispute() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.item_set_milestones(
+        item_id,
+        vec![
+            ItemMilestone {
+                description: "Sketch".to_string(),
+                share_bps: 3000,
+            },
+            ItemMilestone {
+                description: "Final artwork".to_string(),
+                share_bps: 7000,
+            },
+        ],
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    testing_env!(context.attached_deposit(0).build());
+    contract.milestone_approve(order_id, 0);
+
+    // the arbitration fee comes out of the milestone left
+    contract.start_dispute(order_id, "Final artwork never came".to_string());
+    testing_env!(
+        context.predecessor_account_id(accounts(0)).build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"7".to_vec())],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 7 / 10 * 8 / 10, Ok("7".to_string()));
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.amount, ONE_NEAR * 7 / 10 * 8 / 10);
+    assert_eq!(order.milestones[0].amount, ONE_NEAR * 3 / 10);
+    assert_eq!(order.milestones[1].amount, order.amount);
+}
+
+#[test]
+#[should_panic(expected = "Order cannot be cancelled at this stage")]
+fn test_milestones_buyer_cancel() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.item_set_milestones(
+        item_id,
+        vec![
+            ItemMilestone {
+                description: "Sketch".to_string(),
+                share_bps: 3000,
+            },
+            ItemMilestone {
+                description: "Final artwork".to_string(),
+                share_bps: 7000,
+            },
+        ],
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    testing_env!(context.attached_deposit(0).build());
+    contract.milestone_approve(order_id, 0);
+    contract.order_cancel(order_id);
+}
+
+#[test]
+#[should_panic(expected = "Milestone orders can't be refunded late")]
+fn test_milestones_refund_late() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.item_set_milestones(
+        item_id,
+        vec![
+            ItemMilestone {
+                description: "Sketch".to_string(),
+                share_bps: 3000,
+            },
+            ItemMilestone {
+                description: "Final artwork".to_string(),
+                share_bps: 7000,
+            },
+        ],
+    );
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    testing_env!(context
+        .attached_deposit(0)
+        .block_timestamp((DEFAULT_HANDLING_TIME + 1) * 1_000_000)
+        .build());
+    contract.order_refund_late(order_id);
+}
+
+#[test]
+#[should_panic(expected = "Milestone shares must add up to 10000 basis points")]
+fn test_milestones_shares() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.item_set_milestones(
+        item_id,
+        vec![ItemMilestone {
+            description: "Sketch".to_string(),
+            share_bps: 3000,
+        }],
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "The arbitration fee of a NEAR order is paid from the escrow, attach no deposit"
+)]
+fn test_dispute_deposit() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.start_dispute(order_id, "Item never arrived".to_string());
+}
+
+#[test]
+fn test_settlement_retry() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    // the order is settling until the arbiter answers
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.start_dispute(order_id, "Item never arrived".to_string());
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::Settling
+    );
+
+    // a failed arbiter call can be retried
+    testing_env!(
+        context
+            .predecessor_account_id(accounts(0))
+            .current_account_id(accounts(0))
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Failed],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Err(PromiseError::Failed));
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::SettlementFailed
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.settlement_retry(order_id);
+
+    testing_env!(
+        context.predecessor_account_id(accounts(0)).build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"7".to_vec())],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Ok("7".to_string()));
+    let order = contract.get_order(order_id).unwrap();
+    assert_eq!(order.status, OrderStatus::Disputed);
+    assert_eq!(order.dispute_id, Some(7));
+    assert_eq!(order.amount, ONE_NEAR * 8 / 10);
+    assert!(order.settlement.is_none());
+}
+
+#[test]
+fn test_settlement_cancel() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    // the order is settling until the arbiter answers
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.start_dispute(order_id, "Item never arrived".to_string());
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::Settling
+    );
+
+    // the arbiter call fails
+    testing_env!(
+        context
+            .predecessor_account_id(accounts(0))
+            .current_account_id(accounts(0))
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Failed],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Err(PromiseError::Failed));
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::SettlementFailed
+    );
+
+    assert_eq!(contract.open_disputes, 1);
+
+    // the buyer gives up, the order is shipped again and no longer blocks an arbiter change
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.settlement_cancel(order_id);
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::Shipped
+    );
+    assert_eq!(contract.open_disputes, 0);
+}
+
+#[test]
+fn test_arbiter_change() {
+    let store_id: AccountId = "store.factory.testnet".parse().unwrap();
+    let new_arbiter_id: AccountId = "arbiter2.testnet".parse().unwrap();
+    let mut context = get_context(accounts(0));
+    testing_env!(context
+        .current_account_id(store_id.clone())
+        .attached_deposit(ONE_NEAR)
+        .build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.start_dispute(order_id, "Item never arrived".to_string());
+    testing_env!(
+        context.predecessor_account_id(store_id.clone()).build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"7".to_vec())],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Ok("7".to_string()));
+    assert_eq!(contract.open_disputes, 1);
+
+    // arbiters the factory doesn't allow are dropped
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.arbiter_propose(accounts(3));
+    testing_env!(
+        context.predecessor_account_id(store_id.clone()).build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"false".to_vec())],
+    );
+    assert!(!contract.arbiter_propose_callback(accounts(3), Ok(false)));
+    assert_eq!(contract.get_pending_arbiter(), None);
+
+    // the current arbiter keeps the store until its dispute is resolved
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.arbiter_propose(new_arbiter_id.clone());
+    testing_env!(
+        context.predecessor_account_id(store_id).build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"true".to_vec())],
+    );
+    assert!(contract.arbiter_propose_callback(new_arbiter_id.clone(), Ok(true)));
+    assert_eq!(contract.get_pending_arbiter(), Some(new_arbiter_id.clone()));
+    assert_eq!(contract.get_store_arbiter(), sample_arbiter());
+
+    testing_env!(context.predecessor_account_id(sample_arbiter()).build());
+    contract.dispute_resolve(order_id, DisputeResolution::BuyerWon);
+    assert_eq!(contract.open_disputes, 0);
+    assert_eq!(contract.get_pending_arbiter(), None);
+    assert_eq!(contract.get_store_arbiter(), new_arbiter_id);
+    assert!(get_logs()
+        .last()
+        .unwrap()
+        .contains(&format!("\"arbiter_id\":\"{}\"", new_arbiter_id)));
+}
+
+#[test]
+fn test_store_pause() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    // existing orders are still shipped while the store is paused
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .block_timestamp(1_000_000_000)
+        .build());
+    contract.store_pause(Some(5_000));
+    assert_eq!(contract.get_store_pause().unwrap().reopens_at, Some(5_000));
+    assert_eq!(
+        contract.store_metadata().pause.unwrap().reopens_at,
+        Some(5_000)
+    );
+    contract.order_shipped(order_id, sample_shipping());
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::Shipped
+    );
+
+    // the store opens again on its own at the reopening date
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .block_timestamp(5_000_000_000)
+        .build());
+    assert!(contract.get_store_pause().is_none());
+    assert!(contract.store_metadata().pause.is_none());
+    contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.store_pause(None);
+    contract.store_resume();
+    assert!(contract.get_store_pause().is_none());
+}
+
+#[test]
+#[should_panic(expected = "Store is paused")]
+fn test_store_pause_item_buy() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    contract.store_pause(None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, None, None);
+}
+
+#[test]
+fn test_staff() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.staff_grant(accounts(3), StaffRole::CatalogEditor);
+    contract.staff_grant(accounts(4), StaffRole::Fulfilment);
+    assert_eq!(contract.get_staff(None, None).len(), 2);
+
+    // catalog editors create items, the action is recorded with their account
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(3))
+        .build());
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    assert!(get_logs()
+        .last()
+        .unwrap()
+        .contains(&format!("\"actor_id\":\"{}\"", accounts(3))));
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    // fulfilment staff ships the order
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(4))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    assert_eq!(
+        contract.get_order(order_id).unwrap().status,
+        OrderStatus::Shipped
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.staff_revoke(accounts(4));
+    assert_eq!(contract.get_staff_role(accounts(4)), None);
+}
+
+#[test]
+#[should_panic(expected = "Only the owner or staff can ship the order")]
+fn test_staff_permissions() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.staff_grant(accounts(3), StaffRole::CatalogEditor);
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    // catalog editors can't ship
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(3))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+}
+
+#[test]
+#[should_panic(expected = "Only owner can set the return window")]
+fn test_staff_return_window() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.staff_grant(accounts(1), StaffRole::Manager);
+
+    // the return window holds back the owner's payouts, even managers can't set it
+    testing_env!(context.predecessor_account_id(accounts(1)).build());
+    contract.set_return_window(1000);
+}
+
+#[test]
+fn test_ownership_transfer() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context
+        .current_account_id("store.factory.testnet".parse().unwrap())
+        .attached_deposit(ONE_NEAR)
+        .build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+    let used_bytes = contract
+        .storage_accounts
+        .get(&accounts(0))
+        .unwrap()
+        .used_bytes;
+    contract.staff_grant(accounts(1), StaffRole::Manager);
+    contract.staff_grant(accounts(3), StaffRole::CatalogEditor);
+
+    // completed orders don't block the transfer
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+
+    testing_env!(context.predecessor_account_id(accounts(0)).build());
+    contract.ownership_propose(accounts(1));
+    assert_eq!(contract.get_pending_owner(), Some(accounts(1)));
+
+    // the new owner takes over the store's bytes
+    testing_env!(context
+        .attached_deposit(ONE_NEAR)
+        .predecessor_account_id(accounts(1))
+        .build());
+    contract.ownership_accept();
+    assert_eq!(contract.owner_id, accounts(1));
+    assert_eq!(contract.get_pending_owner(), None);
+
+    // the previous owner's staff lose their roles
+    assert_eq!(contract.get_staff_role(accounts(1)), None);
+    assert_eq!(contract.get_staff_role(accounts(3)), None);
+    assert_eq!(
+        contract
+            .storage_accounts
+            .get(&accounts(0))
+            .unwrap()
+            .used_bytes,
+        0
+    );
+    assert!(
+        contract
+            .storage_accounts
+            .get(&accounts(1))
+            .unwrap()
+            .used_bytes
+            >= used_bytes
+    );
+
+    // the factory lists the store under the new owner once it confirms
+    assert_eq!(contract.factory_owner_id, accounts(0));
+    testing_env!(
+        context
+            .predecessor_account_id("store.factory.testnet".parse().unwrap())
+            .build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(vec![])],
+    );
+    contract.ownership_sync_callback(accounts(1));
+    assert_eq!(contract.factory_owner_id, accounts(1));
+}
+
+#[test]
+#[should_panic(expected = "Factory already lists the store under its owner")]
+fn test_ownership_sync() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context
+        .current_account_id("store.factory.testnet".parse().unwrap())
+        .attached_deposit(ONE_NEAR)
+        .build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.ownership_sync();
+}
+
+#[test]
+#[should_panic(expected = "Factory still lists the store under its previous owner")]
+fn test_delete_self_unsynced() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context
+        .current_account_id("store.factory.testnet".parse().unwrap())
+        .attached_deposit(ONE_NEAR)
+        .build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    contract.ownership_propose(accounts(1));
+
+    // the factory hasn't confirmed the move yet
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(1))
+        .build());
+    contract.ownership_accept();
+    contract.delete_self();
+}
+
+#[test]
+#[should_panic(expected = "Can't transfer the store while there are active orders or disputes")]
+fn test_ownership_transfer_active_orders() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.ownership_propose(accounts(1));
+}
+
+#[test]
+fn test_dispute_management() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .predecessor_account_id(accounts(0))
+        .attached_deposit(0)
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    // the arbiter gets 20% of the order with the dispute
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.start_dispute(order_id, "Item never arrived".to_string());
+    testing_env!(
+        context.build(),
+        near_sdk::VMConfig::test(),
+        near_sdk::RuntimeFeesConfig::test(),
+        Default::default(),
+        vec![near_sdk::PromiseResult::Successful(b"0".to_vec())],
+    );
+    contract.start_dispute_callback(order_id, ONE_NEAR * 8 / 10, Ok("0".to_string()));
+
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Disputed.try_to_vec().unwrap()
+    );
+
+    testing_env!(context.predecessor_account_id(sample_arbiter()).build());
+    contract.dispute_resolve(order_id, DisputeResolution::BuyerWon);
+
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Resolved.try_to_vec().unwrap()
+    );
+}
+
+#[test]
+fn test_review_management() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+
+    assert_eq!(
+        contract
+            .get_order(order_id)
+            .unwrap()
+            .status
+            .try_to_vec()
+            .unwrap(),
+        OrderStatus::Completed.try_to_vec().unwrap()
+    );
+
+    let review_id = contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
+
+    let review = contract.get_review(review_id).unwrap();
+
+    assert_eq!(review.reviewer_id, accounts(2));
+    assert_eq!(review.rating, 5);
+    assert_eq!(review.comment, "Awesome".to_string());
+}
+
+#[test]
+fn test_enumeration() {
+    let mut context = get_context(accounts(0));
+    testing_env!(context.attached_deposit(ONE_NEAR).build());
+    let mut contract = Contract::new(
+        accounts(0),
+        sample_store_metadata(),
+        sample_arbiter(),
+        accounts(5),
+        0,
+    );
+    let item_id =
+        contract.item_create(U128(ONE_NEAR), sample_item_metadata(), None, 10, None, None);
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(2))
+        .build());
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(2)).build());
+    contract.order_complete(order_id);
+    contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(3))
+        .build());
+
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(3)).build());
+    contract.order_complete(order_id);
+    let review_id = contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
+
+    assert!(contract.get_review(review_id).is_some());
+
+    testing_env!(context
+        .attached_deposit(ONE_NEAR * 2)
+        .predecessor_account_id(accounts(4))
+        .build());
+
+    let order_id = contract.item_buy(item_id, None, None, None, None);
+
+    testing_env!(context
+        .attached_deposit(0)
+        .predecessor_account_id(accounts(0))
+        .build());
+    contract.order_shipped(order_id, sample_shipping());
+    testing_env!(context.predecessor_account_id(accounts(4)).build());
     contract.order_complete(order_id);
     contract.item_review(item_id.clone(), 5, Some("Awesome".to_string()));
 
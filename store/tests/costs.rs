@@ -28,6 +28,7 @@ async fn test_methods_cost() -> anyhow::Result<()> {
         .expect("Failed to start the worker");
     let account = worker.dev_create_account().await?;
     let account2 = worker.dev_create_account().await?;
+    let arbiter = worker.dev_create_account().await?;
     println!("----------------------------------------");
 
     account
@@ -49,6 +50,9 @@ async fn test_methods_cost() -> anyhow::Result<()> {
         .call(&account.id(), "new")
         .args_json(&json!({
         "owner_id": account.id(),
+        "arbiter_id": arbiter.id(),
+        "treasury_id": account2.id(),
+        "commission_bps": 250,
         "metadata": {
             "name": generate_text(100),
             "category": 1,
@@ -88,7 +92,9 @@ async fn test_methods_cost() -> anyhow::Result<()> {
                 "tags": [generate_text(10), generate_text(10), generate_text(10), generate_text(10)],
             },
             "price": ONE_NEAR.to_string(),
+            "stock": 10,
         }))
+        .deposit(ONE_NEAR / 10)
         .max_gas()
         .transact()
         .await?;
@@ -109,7 +115,7 @@ async fn test_methods_cost() -> anyhow::Result<()> {
         .args_json(&json!({
             "item_id": "0",
         }))
-        .deposit(ONE_NEAR)
+        .deposit(ONE_NEAR + ONE_NEAR / 10)
         .max_gas()
         .transact()
         .await?;